@@ -0,0 +1,304 @@
+use crate::character::Character;
+use crate::classes::DamageType;
+use crate::damage::AttackProfile;
+use crate::status::{StatusEffect, StatusKind};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Targeting {
+    OnSelf,
+    Enemy,
+}
+
+#[derive(Debug, Clone)]
+pub enum AbilityEffect {
+    // Always aimed at the enemy, built on top of the user's regular attack
+    Strike {
+        multiplier: f32,
+        bonus_damage: u32,
+        damage_type: Option<DamageType>,
+    },
+    Heal(u32),
+    ApplyStatus(StatusEffect),
+    Taunt(u32), // Extra threat with the enemy, on top of any damage dealt
+}
+
+#[derive(Debug, Clone)]
+pub struct Ability {
+    pub name: String,
+    pub mana_cost: u32,
+    pub cooldown: u32, // Rounds before it can be used again
+    pub targeting: Targeting,
+    pub effects: Vec<AbilityEffect>,
+}
+
+impl Ability {
+    pub fn strike_profile(
+        &self,
+        user: &dyn Character,
+        multiplier: f32,
+        bonus_damage: u32,
+        damage_type: Option<DamageType>,
+    ) -> AttackProfile {
+        let attack = user.get_attack();
+        AttackProfile {
+            name: Some(self.name.clone()),
+            damage_type: damage_type.unwrap_or(attack.damage_type),
+            attack_type: attack.attack_type,
+            base_damage: (attack.base_damage as f32 * multiplier) as u32 + bonus_damage,
+            mana_cost: 0, // Paid once for the whole ability
+        }
+    }
+
+    // Lowercase name with underscores, e.g. "shield_bash", as used in data files
+    pub fn id(&self) -> String {
+        self.name.to_lowercase().replace(' ', "_")
+    }
+
+    fn heals(&self) -> bool {
+        self.effects
+            .iter()
+            .any(|effect| matches!(effect, AbilityEffect::Heal(_)))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct AbilitySlot {
+    ability: Ability,
+    remaining_cooldown: u32,
+    just_used: bool, // The round it was used in doesn't count towards the cooldown
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Abilities {
+    slots: Vec<AbilitySlot>,
+}
+
+impl Abilities {
+    pub fn from_kit(kit: Vec<Ability>) -> Self {
+        let mut abilities = Abilities::default();
+        for ability in kit {
+            abilities.learn(ability);
+        }
+        abilities
+    }
+
+    pub fn learn(&mut self, ability: Ability) {
+        self.slots.push(AbilitySlot {
+            ability,
+            remaining_cooldown: 0,
+            just_used: false,
+        });
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Ability, u32)> {
+        self.slots
+            .iter()
+            .map(|slot| (&slot.ability, slot.remaining_cooldown))
+    }
+
+    // First ability off cooldown that the user can afford and would benefit from
+    pub fn choose(&self, user: &dyn Character) -> Option<usize> {
+        self.slots.iter().position(|slot| {
+            let ability = &slot.ability;
+            let wants_healing = user.get_health() < user.get_calculated_max_health() / 2;
+            slot.remaining_cooldown == 0
+                && ability.mana_cost <= user.get_mana()
+                && (!ability.heals() || ability.targeting == Targeting::Enemy || wants_healing)
+        })
+    }
+
+    // Starts the ability's cooldown and hands back a copy to resolve
+    pub fn trigger(&mut self, index: usize) -> Option<Ability> {
+        let slot = self.slots.get_mut(index)?;
+        slot.remaining_cooldown = slot.ability.cooldown;
+        slot.just_used = true;
+        Some(slot.ability.clone())
+    }
+
+    pub fn tick_cooldowns(&mut self) {
+        for slot in &mut self.slots {
+            if slot.just_used {
+                slot.just_used = false;
+            } else {
+                slot.remaining_cooldown = slot.remaining_cooldown.saturating_sub(1);
+            }
+        }
+    }
+
+    pub fn reset_cooldowns(&mut self) {
+        for slot in &mut self.slots {
+            slot.remaining_cooldown = 0;
+            slot.just_used = false;
+        }
+    }
+}
+
+pub fn berserker_rage(strength: u32) -> Ability {
+    Ability {
+        name: "Berserker Rage".to_string(),
+        mana_cost: 0,
+        cooldown: 3,
+        targeting: Targeting::Enemy,
+        effects: vec![AbilityEffect::Strike {
+            multiplier: 1.0,
+            bonus_damage: strength * 2,
+            damage_type: None,
+        }],
+    }
+}
+
+pub fn warrior_starter_kit() -> Vec<Ability> {
+    vec![
+        Ability {
+            name: "Shield Bash".to_string(),
+            mana_cost: 0,
+            cooldown: 4,
+            targeting: Targeting::Enemy,
+            effects: vec![
+                AbilityEffect::Strike {
+                    multiplier: 0.6,
+                    bonus_damage: 0,
+                    damage_type: Some(DamageType::Blunt),
+                },
+                AbilityEffect::ApplyStatus(StatusEffect::new(StatusKind::Stun, 0, 1)),
+                AbilityEffect::Taunt(40),
+            ],
+        },
+        Ability {
+            name: "Rending Strike".to_string(),
+            mana_cost: 0,
+            cooldown: 3,
+            targeting: Targeting::Enemy,
+            effects: vec![
+                AbilityEffect::Strike {
+                    multiplier: 1.0,
+                    bonus_damage: 0,
+                    damage_type: Some(DamageType::Slash),
+                },
+                AbilityEffect::ApplyStatus(StatusEffect::new(StatusKind::Bleed, 4, 3)),
+            ],
+        },
+        Ability {
+            name: "Second Wind".to_string(),
+            mana_cost: 0,
+            cooldown: 6,
+            targeting: Targeting::OnSelf,
+            effects: vec![AbilityEffect::Heal(30)],
+        },
+    ]
+}
+
+pub fn mage_starter_kit() -> Vec<Ability> {
+    vec![
+        Ability {
+            name: "Arcane Shield".to_string(),
+            mana_cost: 20,
+            cooldown: 5,
+            targeting: Targeting::OnSelf,
+            effects: vec![AbilityEffect::ApplyStatus(StatusEffect::new(
+                StatusKind::Shield,
+                30,
+                3,
+            ))],
+        },
+        Ability {
+            name: "Fireball".to_string(),
+            mana_cost: 25,
+            cooldown: 3,
+            targeting: Targeting::Enemy,
+            effects: vec![
+                AbilityEffect::Strike {
+                    multiplier: 1.5,
+                    bonus_damage: 0,
+                    damage_type: Some(DamageType::Fire),
+                },
+                AbilityEffect::ApplyStatus(StatusEffect::new(StatusKind::Burn, 5, 2)),
+            ],
+        },
+        Ability {
+            name: "Frost Nova".to_string(),
+            mana_cost: 15,
+            cooldown: 4,
+            targeting: Targeting::Enemy,
+            effects: vec![
+                AbilityEffect::Strike {
+                    multiplier: 0.8,
+                    bonus_damage: 0,
+                    damage_type: Some(DamageType::Frost),
+                },
+                AbilityEffect::ApplyStatus(StatusEffect::new(StatusKind::Slow, 30, 2)),
+            ],
+        },
+    ]
+}
+
+// Any ability from the built-in kits; Berserker Rage scales with the given strength
+pub fn ability_by_id(id: &str, strength: u32) -> Option<Ability> {
+    std::iter::once(berserker_rage(strength))
+        .chain(warrior_starter_kit())
+        .chain(mage_starter_kit())
+        .find(|ability| ability.id() == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::players::{PlayerMage, PlayerWarrior};
+
+    #[test]
+    fn a_cooldown_blocks_that_many_full_rounds() {
+        let mut abilities = Abilities::from_kit(vec![berserker_rage(10)]);
+        let user = PlayerWarrior::new("Gimli");
+        assert_eq!(abilities.choose(&user), Some(0));
+
+        abilities.trigger(0);
+        for _ in 0..3 {
+            abilities.tick_cooldowns();
+            assert_eq!(abilities.choose(&user), None);
+        }
+        abilities.tick_cooldowns();
+        assert_eq!(abilities.choose(&user), Some(0));
+    }
+
+    #[test]
+    fn resetting_makes_everything_ready() {
+        let mut abilities = Abilities::from_kit(vec![berserker_rage(10)]);
+        abilities.trigger(0);
+        abilities.reset_cooldowns();
+        abilities.tick_cooldowns();
+        assert!(abilities.iter().all(|(_, cooldown)| cooldown == 0));
+    }
+
+    #[test]
+    fn the_first_ready_ability_in_kit_order_is_chosen() {
+        let mut abilities = Abilities::from_kit(warrior_starter_kit());
+        let user = PlayerWarrior::new("Gimli");
+        assert_eq!(abilities.choose(&user), Some(0)); // Shield Bash
+
+        abilities.trigger(0);
+        assert_eq!(abilities.choose(&user), Some(1)); // Rending Strike
+        abilities.trigger(1);
+        assert_eq!(abilities.choose(&user), None);
+    }
+
+    #[test]
+    fn healing_waits_until_below_half_health() {
+        let abilities = Abilities::from_kit(vec![warrior_starter_kit().remove(2)]);
+        let mut user = PlayerWarrior::new("Gimli");
+        assert_eq!(abilities.choose(&user), None);
+
+        user.set_health(user.get_calculated_max_health() / 2 - 1);
+        assert_eq!(abilities.choose(&user), Some(0));
+    }
+
+    #[test]
+    fn unaffordable_abilities_are_skipped() {
+        let abilities = Abilities::from_kit(mage_starter_kit());
+        let mut user = PlayerMage::new("Saruman");
+        user.set_mana(15);
+        assert_eq!(abilities.choose(&user), Some(2)); // Only Frost Nova is affordable
+
+        user.set_mana(10);
+        assert_eq!(abilities.choose(&user), None);
+    }
+}
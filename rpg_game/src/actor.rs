@@ -0,0 +1,405 @@
+use crate::abilities::{Abilities, ability_by_id};
+use crate::character::{Character, CharacterType, NPCImportance};
+use crate::classes::{AttackType, ClassType, DamageType, Warrior};
+use crate::damage::AttackProfile;
+use crate::elements::{Affinities, Affinity};
+use crate::enemies::{self, Enemy};
+use crate::items::{Inventory, STOCK_MAX_WEIGHT, STOCK_SLOTS};
+use crate::npcs::{self, NPC};
+use crate::players::Player;
+use crate::status::StatusEffects;
+use crate::threat::ThreatTable;
+
+// Everything needed to spawn a character. Unset defenses and speed fall back to the
+// class values.
+#[derive(Debug, Clone)]
+pub struct ActorTemplate {
+    pub id: String,
+    pub name: String,
+    pub character_type: CharacterType,
+    pub class: ClassType,
+    pub base_health: u32,
+    pub base_damage: u32,
+    pub damage_type: DamageType,
+    pub attack_type: AttackType,
+    pub attack_name: Option<String>,
+    pub attack_cost: u32,
+    pub mana: Option<u32>, // Defaults to the class mana pool
+    pub armor: Option<u32>,
+    pub magic_resistance: Option<u32>,
+    pub speed: Option<u32>,
+    pub aggro: u32,
+    pub flee_threshold: f32,
+    pub invincible_above: Option<f32>, // Holds back while above this share of max health
+    pub passive: bool,                 // Never attacks, like merchants and villagers
+    pub trades: bool,
+    pub gives_quests: bool,
+    pub gold: u32,
+    pub dialogue: Option<String>,
+    pub abilities: Vec<String>,
+    pub items: Vec<(String, u32)>, // Item ID and count, packed on spawn
+    pub quests: Vec<String>,       // Quest IDs offered, when gives_quests is set
+    pub affinities: Vec<(DamageType, Affinity)>,
+}
+
+impl ActorTemplate {
+    pub fn new(id: &str, name: &str) -> Self {
+        ActorTemplate {
+            id: id.to_string(),
+            name: name.to_string(),
+            character_type: CharacterType::Enemy { threat_level: 1 },
+            class: ClassType::Warrior(Warrior::new()),
+            base_health: 50,
+            base_damage: 10,
+            damage_type: DamageType::Physical,
+            attack_type: AttackType::Melee,
+            attack_name: None,
+            attack_cost: 0,
+            mana: None,
+            armor: None,
+            magic_resistance: None,
+            speed: None,
+            aggro: 50,
+            flee_threshold: 0.0,
+            invincible_above: None,
+            passive: false,
+            trades: false,
+            gives_quests: false,
+            gold: 0,
+            dialogue: None,
+            abilities: Vec::new(),
+            items: Vec::new(),
+            quests: Vec::new(),
+            affinities: Vec::new(),
+        }
+    }
+
+    pub fn empty_inventory(&self) -> Inventory {
+        if self.trades {
+            Inventory::new(STOCK_SLOTS, STOCK_MAX_WEIGHT)
+        } else {
+            Inventory::default()
+        }
+    }
+
+    pub fn spawn(&self) -> Actor {
+        self.spawn_named(&self.name)
+    }
+
+    pub fn spawn_named(&self, name: &str) -> Actor {
+        let strength = self.class.get_strength().unwrap_or(0);
+        let abilities = self
+            .abilities
+            .iter()
+            .filter_map(|id| ability_by_id(id, strength))
+            .collect();
+        let max_mana = self.mana.or(self.class.get_mana()).unwrap_or(0);
+        let mut inventory = self.empty_inventory();
+        for (id, count) in &self.items {
+            let _ = inventory.add_by_id(id, *count); // Templates are checked when loaded
+        }
+
+        let mut actor = Actor {
+            name: name.to_string(),
+            health: 0,
+            max_health: 0,
+            mana: max_mana,
+            max_mana,
+            experience: 0,
+            gold: self.gold,
+            template: self.clone(),
+            status_effects: StatusEffects::default(),
+            abilities: Abilities::from_kit(abilities),
+            inventory,
+            threat_table: ThreatTable::default(),
+        };
+        actor.max_health = actor.get_calculated_max_health();
+        actor.health = actor.max_health;
+        actor
+    }
+}
+
+// A character whose behaviour comes entirely from its template, usable wherever a
+// Player, Enemy or NPC is expected
+#[derive(Debug, Clone)]
+pub struct Actor {
+    pub name: String,
+    pub health: u32,
+    pub max_health: u32,
+    pub mana: u32,
+    pub max_mana: u32,
+    pub experience: u32,
+    pub gold: u32,
+    pub template: ActorTemplate,
+    pub status_effects: StatusEffects,
+    pub abilities: Abilities,
+    pub inventory: Inventory,
+    pub threat_table: ThreatTable,
+}
+
+impl Character for Actor {
+    fn new(name: &str) -> Self {
+        ActorTemplate::new("actor", name).spawn()
+    }
+
+    fn revive(&mut self) {
+        if self.health == 0 {
+            self.health = self.get_calculated_max_health();
+            self.status_effects.on_revive();
+            self.abilities.reset_cooldowns();
+            self.threat_table.clear();
+            self.mana = self.max_mana;
+        }
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_health(&self) -> u32 {
+        self.health
+    }
+
+    fn set_health(&mut self, health: u32) {
+        self.health = health.min(self.max_health);
+    }
+
+    fn get_class_type(&self) -> &ClassType {
+        &self.template.class
+    }
+
+    fn get_character_type(&self) -> &CharacterType {
+        &self.template.character_type
+    }
+
+    fn get_status_effects(&self) -> &StatusEffects {
+        &self.status_effects
+    }
+
+    fn get_status_effects_mut(&mut self) -> &mut StatusEffects {
+        &mut self.status_effects
+    }
+
+    fn get_abilities(&self) -> &Abilities {
+        &self.abilities
+    }
+
+    fn get_abilities_mut(&mut self) -> &mut Abilities {
+        &mut self.abilities
+    }
+
+    fn get_inventory(&self) -> Option<&Inventory> {
+        Some(&self.inventory)
+    }
+
+    fn get_inventory_mut(&mut self) -> Option<&mut Inventory> {
+        Some(&mut self.inventory)
+    }
+
+    fn get_base_health(&self) -> u32 {
+        self.template.base_health
+    }
+
+    fn is_invincible(&self) -> bool {
+        self.template
+            .invincible_above
+            .is_some_and(|share| self.health as f32 > self.max_health as f32 * share)
+    }
+
+    fn get_damage_type(&self) -> DamageType {
+        self.template.damage_type
+    }
+
+    fn get_attack_type(&self) -> AttackType {
+        self.template.attack_type
+    }
+
+    fn get_base_damage(&self) -> u32 {
+        self.template.base_damage
+    }
+
+    fn get_mana(&self) -> u32 {
+        self.mana
+    }
+
+    fn get_max_mana(&self) -> u32 {
+        self.max_mana
+    }
+
+    fn set_mana(&mut self, mana: u32) {
+        self.mana = mana.min(self.max_mana);
+    }
+
+    fn get_gold(&self) -> u32 {
+        self.gold
+    }
+
+    fn set_gold(&mut self, gold: u32) {
+        self.gold = gold;
+    }
+
+    fn get_mana_regen(&self) -> u32 {
+        self.template.class.get_power().unwrap_or(0) / 2
+    }
+
+    fn get_attack(&self) -> AttackProfile {
+        AttackProfile {
+            name: self.template.attack_name.clone(),
+            damage_type: self.get_damage_type(),
+            attack_type: self.get_attack_type(),
+            base_damage: self.get_base_damage(),
+            mana_cost: self.template.attack_cost,
+        }
+    }
+
+    fn get_armor(&self) -> u32 {
+        self.template
+            .armor
+            .unwrap_or_else(|| self.template.class.get_armor())
+    }
+
+    fn get_magic_resistance(&self) -> u32 {
+        self.template
+            .magic_resistance
+            .unwrap_or_else(|| self.template.class.get_magic_resistance())
+    }
+
+    fn get_affinities(&self) -> Affinities {
+        self.template.affinities.iter().fold(
+            Affinities::default(),
+            |affinities, &(damage_type, affinity)| affinities.with(damage_type, affinity),
+        )
+    }
+
+    fn get_speed(&self) -> u32 {
+        self.template
+            .speed
+            .unwrap_or_else(|| self.template.class.get_speed())
+    }
+
+    fn can_attack(&self) -> bool {
+        !self.template.passive && self.get_health() > 0 && !self.is_invincible()
+    }
+
+    fn get_flee_threshold(&self) -> f32 {
+        self.template.flee_threshold
+    }
+}
+
+impl Player for Actor {
+    // Only player actors track a level; everyone else just gets a full heal
+    fn level_up(&mut self) {
+        if let CharacterType::Player { level } = &mut self.template.character_type {
+            *level += 1;
+        }
+        self.max_health = self.get_calculated_max_health();
+        self.health = self.max_health;
+    }
+
+    fn get_experience(&self) -> u32 {
+        self.experience
+    }
+
+    fn get_level(&self) -> u32 {
+        match self.template.character_type {
+            CharacterType::Player { level } => level,
+            _ => 1,
+        }
+    }
+
+    fn add_experience(&mut self, exp: u32) {
+        self.experience += exp;
+        while self.experience >= self.get_level() * 100 {
+            self.experience -= self.get_level() * 100;
+            self.level_up();
+        }
+    }
+}
+
+impl Enemy for Actor {
+    fn get_id(&self) -> &str {
+        &self.template.id
+    }
+
+    fn get_aggro(&self) -> u32 {
+        self.template.aggro
+    }
+
+    // Only enemies are ever a threat
+    fn get_threat_level(&self) -> u32 {
+        match self.template.character_type {
+            CharacterType::Enemy { threat_level } => threat_level,
+            _ => 0,
+        }
+    }
+
+    fn get_threat_table(&self) -> &ThreatTable {
+        &self.threat_table
+    }
+
+    fn get_threat_table_mut(&mut self) -> &mut ThreatTable {
+        &mut self.threat_table
+    }
+}
+
+impl NPC for Actor {
+    fn get_dialogue(&self) -> &str {
+        self.template.dialogue.as_deref().unwrap_or("...")
+    }
+
+    fn get_importance(&self) -> &NPCImportance {
+        if let CharacterType::NPC { importance } = &self.template.character_type {
+            importance
+        } else {
+            &NPCImportance::Minor
+        }
+    }
+
+    fn can_trade(&self) -> bool {
+        self.template.trades
+    }
+
+    fn can_give_quests(&self) -> bool {
+        self.template.gives_quests
+    }
+}
+
+// The built-in enemies and NPCs, each just a template for an Actor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Preset {
+    GoblinWarrior,
+    GoblinMage,
+    DragonBoss,
+    Merchant,
+    QuestGiver,
+    LegendaryNPC,
+    Villager,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 7] = [
+        Preset::GoblinWarrior,
+        Preset::GoblinMage,
+        Preset::DragonBoss,
+        Preset::Merchant,
+        Preset::QuestGiver,
+        Preset::LegendaryNPC,
+        Preset::Villager,
+    ];
+
+    pub fn template(&self) -> ActorTemplate {
+        match self {
+            Preset::GoblinWarrior => enemies::goblin_warrior(),
+            Preset::GoblinMage => enemies::goblin_mage(),
+            Preset::DragonBoss => enemies::dragon_boss(),
+            Preset::Merchant => npcs::merchant(),
+            Preset::QuestGiver => npcs::quest_giver(),
+            Preset::LegendaryNPC => npcs::legendary_npc(),
+            Preset::Villager => npcs::villager(),
+        }
+    }
+
+    pub fn spawn(&self, name: &str) -> Actor {
+        self.template().spawn_named(name)
+    }
+}
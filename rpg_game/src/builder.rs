@@ -0,0 +1,463 @@
+use std::fmt;
+
+use crate::abilities::ability_by_id;
+use crate::actor::{Actor, ActorTemplate, Preset};
+use crate::character::{Character, CharacterType, NPCImportance};
+use crate::classes::{ClassType, Mage, Warrior};
+use crate::equipment::EquipError;
+use crate::items::InventoryError;
+use crate::players::{PlayerMage, PlayerWarrior};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildError {
+    ZeroLevel,
+    ZeroHealth,
+    // Level, threat level and importance each pick a different kind of character
+    ConflictingType {
+        first: &'static str,
+        second: &'static str,
+    },
+    NotAvailable {
+        setting: &'static str,
+        target: &'static str,
+    },
+    ClassMismatch {
+        stat: &'static str,
+        class: &'static str,
+    },
+    HealthAboveMax {
+        health: u32,
+        max_health: u32,
+    },
+    UnknownAbility(String),
+    Inventory(InventoryError),
+    Equip(EquipError),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuildError::ZeroLevel => write!(f, "levels start at 1"),
+            BuildError::ZeroHealth => write!(f, "characters can't start with 0 health"),
+            BuildError::ConflictingType { first, second } => {
+                write!(f, "can't set both {} and {}", first, second)
+            }
+            BuildError::NotAvailable { setting, target } => {
+                write!(f, "{} can't be set on {}", setting, target)
+            }
+            BuildError::ClassMismatch { stat, class } => {
+                write!(f, "a {} has no {}", class, stat)
+            }
+            BuildError::HealthAboveMax { health, max_health } => write!(
+                f,
+                "starting health {} is above the maximum of {}",
+                health, max_health
+            ),
+            BuildError::UnknownAbility(id) => write!(f, "unknown ability `{}`", id),
+            BuildError::Inventory(error) => write!(f, "can't pack starting items: {}", error),
+            BuildError::Equip(error) => write!(f, "can't equip starting gear: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BuildError::Inventory(error) => Some(error),
+            BuildError::Equip(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+fn class_name(class: &ClassType) -> &'static str {
+    match class {
+        ClassType::Warrior(_) => "warrior",
+        ClassType::Mage(_) => "mage",
+    }
+}
+
+// Collects overrides for a new character; nothing is checked until one of the
+// build methods is called
+#[derive(Debug, Clone, Default)]
+pub struct CharacterBuilder {
+    name: String,
+    template: Option<ActorTemplate>,
+    level: Option<u32>,
+    threat_level: Option<u32>,
+    importance: Option<NPCImportance>,
+    base_health: Option<u32>,
+    health: Option<u32>,
+    strength: Option<u32>,
+    mana: Option<u32>,
+    power: Option<u32>,
+    abilities: Vec<String>,
+    items: Vec<(String, u32)>,
+    gear: Vec<String>,
+    gold: Option<u32>,
+}
+
+impl CharacterBuilder {
+    pub fn new(name: &str) -> Self {
+        CharacterBuilder {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn from_template(template: ActorTemplate, name: &str) -> Self {
+        CharacterBuilder {
+            template: Some(template),
+            ..Self::new(name)
+        }
+    }
+
+    pub fn from_preset(preset: Preset, name: &str) -> Self {
+        Self::from_template(preset.template(), name)
+    }
+
+    pub fn with_level(mut self, level: u32) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    pub fn with_threat_level(mut self, threat_level: u32) -> Self {
+        self.threat_level = Some(threat_level);
+        self
+    }
+
+    pub fn with_importance(mut self, importance: NPCImportance) -> Self {
+        self.importance = Some(importance);
+        self
+    }
+
+    pub fn with_base_health(mut self, base_health: u32) -> Self {
+        self.base_health = Some(base_health);
+        self
+    }
+
+    // Starting health, e.g. to begin an encounter already wounded
+    pub fn with_health(mut self, health: u32) -> Self {
+        self.health = Some(health);
+        self
+    }
+
+    pub fn with_strength(mut self, strength: u32) -> Self {
+        self.strength = Some(strength);
+        self
+    }
+
+    pub fn with_mana(mut self, mana: u32) -> Self {
+        self.mana = Some(mana);
+        self
+    }
+
+    pub fn with_power(mut self, power: u32) -> Self {
+        self.power = Some(power);
+        self
+    }
+
+    // Learned on top of whatever the character already starts with
+    pub fn with_ability(mut self, id: &str) -> Self {
+        self.abilities.push(id.to_string());
+        self
+    }
+
+    // Packed after anything the template already carries
+    pub fn with_item(mut self, id: &str, quantity: u32) -> Self {
+        self.items.push((id.to_string(), quantity));
+        self
+    }
+
+    // Packed and then put on, so class restrictions apply as they would in play
+    pub fn with_gear(mut self, id: &str) -> Self {
+        self.gear.push(id.to_string());
+        self
+    }
+
+    pub fn with_gold(mut self, gold: u32) -> Self {
+        self.gold = Some(gold);
+        self
+    }
+
+    pub fn build_warrior(&self) -> Result<PlayerWarrior, BuildError> {
+        self.check_player()?;
+        let class = self.class(&ClassType::Warrior(Warrior::new()))?;
+
+        let mut warrior = PlayerWarrior::new(&self.name);
+        warrior.level = self.player_level()?;
+        warrior.character_type = CharacterType::Player {
+            level: warrior.level,
+        };
+        warrior.class = class;
+        warrior.max_health = warrior.get_calculated_max_health();
+        warrior.gold = self.gold.unwrap_or(0);
+        self.learn_abilities(&mut warrior)?;
+        self.pack_items(&mut warrior)?;
+        for id in &self.gear {
+            Self::pack_gear(&mut warrior, id)?;
+            warrior.equip(id).map_err(BuildError::Equip)?;
+        }
+        warrior.health = self.starting_health(warrior.max_health)?;
+        Ok(warrior)
+    }
+
+    pub fn build_mage(&self) -> Result<PlayerMage, BuildError> {
+        self.check_player()?;
+        let class = self.class(&ClassType::Mage(Mage::new()))?;
+
+        let mut mage = PlayerMage::new(&self.name);
+        mage.level = self.player_level()?;
+        mage.character_type = CharacterType::Player { level: mage.level };
+        mage.class = class;
+        mage.max_mana = mage.calculate_max_mana();
+        mage.mana = mage.max_mana;
+        mage.max_health = mage.get_calculated_max_health();
+        mage.gold = self.gold.unwrap_or(0);
+        self.learn_abilities(&mut mage)?;
+        self.pack_items(&mut mage)?;
+        for id in &self.gear {
+            Self::pack_gear(&mut mage, id)?;
+            mage.equip(id).map_err(BuildError::Equip)?;
+        }
+        mage.health = self.starting_health(mage.max_health)?;
+        Ok(mage)
+    }
+
+    // Without a template the class follows the stats given: mana or power make a mage
+    pub fn build_actor(&self) -> Result<Actor, BuildError> {
+        if !self.gear.is_empty() {
+            return Err(BuildError::NotAvailable {
+                setting: "gear",
+                target: "actors",
+            });
+        }
+        let mut template = match &self.template {
+            Some(template) => template.clone(),
+            None => {
+                let mut template = ActorTemplate::new("custom", &self.name);
+                if self.mana.is_some() || self.power.is_some() {
+                    template.class = ClassType::Mage(Mage::new());
+                }
+                template
+            }
+        };
+
+        if let Some(character_type) = self.character_type(&template.character_type)? {
+            template.character_type = character_type;
+        }
+        template.class = self.class(&template.class)?;
+        if self.mana.is_some() {
+            template.mana = None; // The new class pool replaces the template's
+        }
+        if let Some(base_health) = self.base_health {
+            if base_health == 0 {
+                return Err(BuildError::ZeroHealth);
+            }
+            template.base_health = base_health;
+        }
+        if let Some(gold) = self.gold {
+            template.gold = gold;
+        }
+        for id in &self.abilities {
+            if ability_by_id(id, 0).is_none() {
+                return Err(BuildError::UnknownAbility(id.clone()));
+            }
+            template.abilities.push(id.clone());
+        }
+
+        let mut actor = template.spawn_named(&self.name);
+        actor.health = self.starting_health(actor.max_health)?;
+        self.pack_items(&mut actor)?;
+        Ok(actor)
+    }
+
+    // Players come from their class alone, not from templates
+    fn check_player(&self) -> Result<(), BuildError> {
+        let unavailable = [
+            ("a template", self.template.is_some()),
+            ("a threat level", self.threat_level.is_some()),
+            ("an importance", self.importance.is_some()),
+            ("base health", self.base_health.is_some()),
+        ];
+        match unavailable.iter().find(|(_, set)| *set) {
+            Some((setting, _)) => Err(BuildError::NotAvailable {
+                setting,
+                target: "players",
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn player_level(&self) -> Result<u32, BuildError> {
+        match self.level {
+            Some(0) => Err(BuildError::ZeroLevel),
+            level => Ok(level.unwrap_or(1)),
+        }
+    }
+
+    // None keeps the template's own type. Overrides can't turn an enemy into an NPC
+    // or the other way around.
+    fn character_type(&self, base: &CharacterType) -> Result<Option<CharacterType>, BuildError> {
+        let chosen = [
+            ("a level", self.level.is_some()),
+            ("a threat level", self.threat_level.is_some()),
+            ("an importance", self.importance.is_some()),
+        ];
+        let mut set = chosen.iter().filter(|(_, set)| *set).map(|(name, _)| *name);
+        if let (Some(first), Some(second)) = (set.next(), set.next()) {
+            return Err(BuildError::ConflictingType { first, second });
+        }
+
+        let target = match base {
+            CharacterType::Player { .. } => "players",
+            CharacterType::Enemy { .. } => "enemies",
+            CharacterType::NPC { .. } => "NPCs",
+        };
+        let custom = self.template.is_none();
+        let not_available = |setting| Err(BuildError::NotAvailable { setting, target });
+        match (self.level, self.threat_level, &self.importance) {
+            (Some(0), _, _) | (_, Some(0), _) => Err(BuildError::ZeroLevel),
+            (Some(level), _, _) if custom || matches!(base, CharacterType::Player { .. }) => {
+                Ok(Some(CharacterType::Player { level }))
+            }
+            (Some(_), _, _) => not_available("a level"),
+            (_, Some(threat_level), _) if custom || matches!(base, CharacterType::Enemy { .. }) => {
+                Ok(Some(CharacterType::Enemy { threat_level }))
+            }
+            (_, Some(_), _) => not_available("a threat level"),
+            (_, _, Some(importance)) if custom || matches!(base, CharacterType::NPC { .. }) => {
+                Ok(Some(CharacterType::NPC {
+                    importance: importance.clone(),
+                }))
+            }
+            (_, _, Some(_)) => not_available("an importance"),
+            (None, None, None) => Ok(None),
+        }
+    }
+
+    fn class(&self, base: &ClassType) -> Result<ClassType, BuildError> {
+        match base {
+            ClassType::Warrior(warrior) => {
+                if let Some(stat) = self.mage_stat() {
+                    return Err(BuildError::ClassMismatch {
+                        stat,
+                        class: class_name(base),
+                    });
+                }
+                Ok(ClassType::Warrior(Warrior::with_strength(
+                    self.strength.unwrap_or(warrior.get_strength()),
+                )))
+            }
+            ClassType::Mage(mage) => {
+                if self.strength.is_some() {
+                    return Err(BuildError::ClassMismatch {
+                        stat: "strength",
+                        class: class_name(base),
+                    });
+                }
+                Ok(ClassType::Mage(Mage::with_stats(
+                    self.mana.unwrap_or(mage.get_mana()),
+                    self.power.unwrap_or(mage.get_power()),
+                )))
+            }
+        }
+    }
+
+    fn mage_stat(&self) -> Option<&'static str> {
+        if self.mana.is_some() {
+            Some("mana")
+        } else if self.power.is_some() {
+            Some("power")
+        } else {
+            None
+        }
+    }
+
+    fn starting_health(&self, max_health: u32) -> Result<u32, BuildError> {
+        match self.health {
+            Some(0) => Err(BuildError::ZeroHealth),
+            Some(health) if health > max_health => {
+                Err(BuildError::HealthAboveMax { health, max_health })
+            }
+            health => Ok(health.unwrap_or(max_health)),
+        }
+    }
+
+    fn learn_abilities(&self, character: &mut dyn Character) -> Result<(), BuildError> {
+        let strength = character.get_class_type().get_strength().unwrap_or(0);
+        for id in &self.abilities {
+            let ability = ability_by_id(id, strength)
+                .ok_or_else(|| BuildError::UnknownAbility(id.clone()))?;
+            character.get_abilities_mut().learn(ability);
+        }
+        Ok(())
+    }
+
+    fn pack_gear(character: &mut dyn Character, id: &str) -> Result<(), BuildError> {
+        character
+            .get_inventory_mut()
+            .ok_or(BuildError::Inventory(InventoryError::NoInventory))?
+            .add_by_id(id, 1)
+            .map_err(BuildError::Inventory)
+    }
+
+    fn pack_items(&self, character: &mut dyn Character) -> Result<(), BuildError> {
+        if self.items.is_empty() {
+            return Ok(());
+        }
+        let inventory = character
+            .get_inventory_mut()
+            .ok_or(BuildError::Inventory(InventoryError::NoInventory))?;
+        for (id, quantity) in &self.items {
+            inventory
+                .add_by_id(id, *quantity)
+                .map_err(BuildError::Inventory)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::equipment::EquipSlot;
+    use crate::players::Player;
+
+    #[test]
+    fn built_mage_has_the_mana_of_a_levelled_one() {
+        let built = CharacterBuilder::new("Built")
+            .with_level(5)
+            .build_mage()
+            .unwrap();
+        let mut levelled = PlayerMage::new("Levelled");
+        for _ in 1..5 {
+            levelled.level_up();
+        }
+        assert_eq!(built.max_mana, levelled.max_mana);
+        assert_eq!(built.mana, built.max_mana);
+    }
+
+    #[test]
+    fn starting_gear_is_worn_and_counted_in_health() {
+        let warrior = CharacterBuilder::new("Turin")
+            .with_gear("chainmail")
+            .build_warrior()
+            .unwrap();
+        assert!(warrior.equipment.get(EquipSlot::Armor).is_some());
+        assert_eq!(warrior.inventory.count("chainmail"), 0);
+        assert!(warrior.max_health > PlayerWarrior::new("Unarmoured").max_health);
+        assert_eq!(warrior.health, warrior.max_health);
+    }
+
+    #[test]
+    fn starting_gear_follows_class_restrictions() {
+        let result = CharacterBuilder::new("Radagast")
+            .with_gear("flamebrand")
+            .build_mage();
+        assert!(matches!(
+            result,
+            Err(BuildError::Equip(EquipError::WrongClass { .. }))
+        ));
+    }
+}
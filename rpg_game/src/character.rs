@@ -1,5 +1,18 @@
+use crate::abilities::{Abilities, Ability, AbilityEffect, Targeting};
 use crate::classes::{AttackType, ClassType, DamageType};
+use crate::config::{BattleConfig, DrawResolution, FirstStrike};
+use crate::damage::{AttackProfile, AttackRoll, DamageBreakdown, DamagePipeline};
+use crate::elements::Affinities;
+use crate::equipment::Equipment;
+use crate::events::{CombatEvent, CombatObserver, EffectSource};
+use crate::initiative::initiative_order;
+use crate::items::{self, Inventory};
+use crate::report::{BattleReport, EndReason, FighterReport};
+use crate::status::{StatusEffect, StatusEffects, StatusKind, StatusTick, tick_status_effects};
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone)]
 pub enum CharacterType {
     Player { level: u32 },
@@ -53,6 +66,19 @@ impl CharacterType {
             },
         }
     }
+
+    // Small-time traders haggle, famous ones know what their goods are worth
+    pub fn get_price_coefficient(&self) -> f32 {
+        match self {
+            CharacterType::NPC { importance } => match importance {
+                NPCImportance::Minor => 0.85,
+                NPCImportance::Normal => 1.0,
+                NPCImportance::Important => 1.15,
+                NPCImportance::Legendary => 1.4,
+            },
+            _ => 1.0,
+        }
+    }
 }
 
 pub trait Character {
@@ -70,6 +96,43 @@ pub trait Character {
 
     fn get_class_type(&self) -> &ClassType;
     fn get_character_type(&self) -> &CharacterType;
+    fn get_status_effects(&self) -> &StatusEffects;
+    fn get_status_effects_mut(&mut self) -> &mut StatusEffects;
+    fn apply_status(&mut self, effect: StatusEffect) {
+        self.get_status_effects_mut().apply(effect);
+    }
+    fn is_stunned(&self) -> bool {
+        self.get_status_effects().has(StatusKind::Stun)
+    }
+    fn get_abilities(&self) -> &Abilities;
+    fn get_abilities_mut(&mut self) -> &mut Abilities;
+    // Characters without a pack can't carry or use items
+    fn get_inventory(&self) -> Option<&Inventory> {
+        None
+    }
+    fn get_inventory_mut(&mut self) -> Option<&mut Inventory> {
+        None
+    }
+    fn get_equipment(&self) -> Option<&Equipment> {
+        None
+    }
+
+    fn get_gold(&self) -> u32 {
+        0
+    }
+    fn set_gold(&mut self, _gold: u32) {}
+    fn spend_gold(&mut self, amount: u32) -> bool {
+        match self.get_gold().checked_sub(amount) {
+            Some(gold) => {
+                self.set_gold(gold);
+                true
+            }
+            None => false,
+        }
+    }
+    fn earn_gold(&mut self, amount: u32) {
+        self.set_gold(self.get_gold().saturating_add(amount));
+    }
     fn get_base_health(&self) -> u32;
     fn get_calculated_max_health(&self) -> u32 {
         let base = self.get_base_health() as f32;
@@ -86,6 +149,97 @@ pub trait Character {
         (base * coefficient) as u32
     }
 
+    fn get_mana(&self) -> u32 {
+        0
+    }
+    fn get_max_mana(&self) -> u32 {
+        0
+    }
+    fn set_mana(&mut self, _mana: u32) {}
+    fn get_mana_regen(&self) -> u32 {
+        0
+    }
+    fn restore_mana(&mut self, amount: u32) {
+        let mana = self
+            .get_mana()
+            .saturating_add(amount)
+            .min(self.get_max_mana());
+        self.set_mana(mana);
+    }
+
+    fn get_attack(&self) -> AttackProfile {
+        AttackProfile {
+            name: None,
+            damage_type: self.get_damage_type(),
+            attack_type: self.get_attack_type(),
+            base_damage: self.get_base_damage(),
+            mana_cost: 0,
+        }
+    }
+    // Used when the regular attack costs more mana than is left
+    fn get_fallback_attack(&self) -> AttackProfile {
+        AttackProfile {
+            name: Some("Staff Strike".to_string()),
+            damage_type: DamageType::Blunt,
+            attack_type: AttackType::Melee,
+            base_damage: self.get_base_damage() / 3,
+            mana_cost: 0,
+        }
+    }
+    fn choose_attack(&self) -> AttackProfile {
+        let attack = self.get_attack();
+        if attack.mana_cost > self.get_mana() {
+            self.get_fallback_attack()
+        } else {
+            attack
+        }
+    }
+
+    fn get_accuracy(&self) -> f32 {
+        self.get_class_type().get_accuracy()
+    }
+    fn get_evasion(&self) -> f32 {
+        self.get_class_type().get_evasion()
+    }
+    fn get_crit_chance(&self) -> f32 {
+        self.get_class_type().get_crit_chance()
+    }
+    fn get_crit_multiplier(&self) -> f32 {
+        1.5
+    }
+
+    // Worn gear adds to the class defenses
+    fn get_armor(&self) -> u32 {
+        let gear = self.get_equipment().map_or(0, Equipment::get_armor);
+        self.get_class_type().get_armor() + gear
+    }
+    fn get_magic_resistance(&self) -> u32 {
+        let gear = self
+            .get_equipment()
+            .map_or(0, Equipment::get_magic_resistance);
+        self.get_class_type().get_magic_resistance() + gear
+    }
+    fn get_defense(&self, damage_type: DamageType) -> u32 {
+        if damage_type.is_physical() {
+            self.get_armor()
+        } else {
+            self.get_magic_resistance()
+        }
+    }
+    fn get_affinities(&self) -> Affinities {
+        self.get_equipment()
+            .map(Equipment::get_affinities)
+            .unwrap_or_default()
+    }
+
+    fn get_speed(&self) -> u32 {
+        self.get_class_type().get_speed()
+    }
+    // Even a fully slowed character keeps a speed of 1 and gets its turn
+    fn get_effective_speed(&self) -> u32 {
+        ((self.get_speed() as f32 * self.get_status_effects().get_slow_factor()) as u32).max(1)
+    }
+
     fn can_attack(&self) -> bool {
         self.get_health() > 0 && !self.is_invincible()
     }
@@ -93,18 +247,54 @@ pub trait Character {
     fn is_alive(&self) -> bool {
         self.get_health() > 0
     }
+
+    // Fraction of max health below which the character runs from a duel
+    fn get_flee_threshold(&self) -> f32 {
+        0.0
+    }
+
+    fn wants_to_flee(&self) -> bool {
+        self.is_alive()
+            && (self.get_health() as f32)
+                < self.get_calculated_max_health() as f32 * self.get_flee_threshold()
+    }
 }
 
-pub fn attack<A, D>(attacker: &A, defender: &mut D) -> u32
+pub fn attack<A, D>(attacker: &mut A, defender: &mut D) -> u32
 where
     A: Character,
     D: Character,
 {
-    if !attacker.can_attack() {
-        return 0;
+    attack_with(&DamagePipeline::default(), attacker, defender).map_or(0, |hit| hit.final_damage)
+}
+
+pub fn attack_with<A, D>(
+    pipeline: &DamagePipeline,
+    attacker: &mut A,
+    defender: &mut D,
+) -> Option<DamageBreakdown>
+where
+    A: Character,
+    D: Character,
+{
+    if !attacker.can_attack() || attacker.is_stunned() {
+        return None;
     }
 
-    let damage = attacker.get_calculated_damage();
+    let attack = attacker.choose_attack();
+    let hit = pipeline.calculate(attacker, defender, &attack, AttackRoll::default());
+    spend_mana(attacker, attack.mana_cost);
+    deal_damage(defender, hit.final_damage);
+    Some(hit)
+}
+
+fn spend_mana<A: Character>(attacker: &mut A, cost: u32) {
+    attacker.set_mana(attacker.get_mana().saturating_sub(cost));
+}
+
+// Returns the damage that got past any shield, including overkill
+fn deal_damage<D: Character>(defender: &mut D, damage: u32) -> u32 {
+    let damage = defender.get_status_effects_mut().absorb(damage);
     if defender.get_health() > damage {
         defender.set_health(defender.get_health() - damage);
     } else {
@@ -113,76 +303,606 @@ where
     damage
 }
 
-pub struct CombatSystem;
+#[derive(Debug, Clone)]
+pub enum AttackOutcome {
+    Hit(DamageBreakdown),
+    Miss,
+}
+
+// What a single turn did, for threat tables and battle reports
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TurnSummary {
+    pub damage_dealt: u32,
+    pub healing_done: u32,
+    pub taunt: u32,
+    pub overkill: u32,
+    pub missed: u32,
+    pub blocked: u32,
+    pub items_used: u32,
+}
+
+const DAMAGE_VARIANCE: f32 = 0.1; // +/-10% damage when combat is randomized
+
+pub struct CombatSystem {
+    pipeline: DamagePipeline,
+    rng: Option<Box<dyn RngCore>>,
+    observers: Vec<Box<dyn CombatObserver>>,
+    config: BattleConfig,
+}
+
+// Normal play rolls from entropy, so no two fights play out the same
+impl Default for CombatSystem {
+    fn default() -> Self {
+        CombatSystem {
+            pipeline: DamagePipeline::default(),
+            rng: Some(Box::new(StdRng::from_entropy())),
+            observers: Vec::new(),
+            config: BattleConfig::default(),
+        }
+    }
+}
 
 impl CombatSystem {
-    pub fn battle<T1, T2>(fighter1: &mut T1, fighter2: &mut T2) -> BattleResult
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_pipeline(pipeline: DamagePipeline) -> Self {
+        CombatSystem {
+            pipeline,
+            ..Self::default()
+        }
+    }
+
+    // A seeded RNG reproduces a fight exactly
+    pub fn with_rng<R: RngCore + 'static>(mut self, rng: R) -> Self {
+        self.rng = Some(Box::new(rng));
+        self
+    }
+
+    // Without an RNG every attack hits for exactly its calculated damage
+    pub fn without_rng(mut self) -> Self {
+        self.rng = None;
+        self
+    }
+
+    pub fn with_config(mut self, config: BattleConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    // Without observers combat runs silently
+    pub fn with_observer<O: CombatObserver + 'static>(mut self, observer: O) -> Self {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
+    pub fn emit(&mut self, event: CombatEvent) {
+        for observer in &mut self.observers {
+            observer.on_event(&event);
+        }
+    }
+
+    fn roll_attack(
+        &mut self,
+        attacker: &dyn Character,
+        defender: &dyn Character,
+    ) -> Option<AttackRoll> {
+        let Some(rng) = self.rng.as_mut() else {
+            return Some(AttackRoll::default());
+        };
+
+        let accuracy = attacker.get_accuracy() * attacker.get_status_effects().get_slow_factor();
+        let evasion = defender.get_evasion() * defender.get_status_effects().get_slow_factor();
+        let hit_chance = (accuracy - evasion).clamp(0.05, 1.0);
+        if !rng.gen_bool(hit_chance as f64) {
+            return None;
+        }
+
+        let crit_chance = attacker.get_crit_chance().clamp(0.0, 1.0);
+        Some(AttackRoll {
+            critical: rng.gen_bool(crit_chance as f64),
+            variance: rng.gen_range(1.0 - DAMAGE_VARIANCE..=1.0 + DAMAGE_VARIANCE),
+        })
+    }
+
+    fn strike<A, D>(
+        &mut self,
+        attacker: &mut A,
+        defender: &mut D,
+        turn: &mut TurnSummary,
+    ) -> Option<AttackOutcome>
     where
-        T1: Character,
-        T2: Character,
+        A: Character,
+        D: Character,
     {
-        let mut rounds = 0;
-        let max_rounds = 100; // Prevent infinite loops
+        if !attacker.can_attack() || attacker.is_stunned() {
+            return None;
+        }
 
-        while fighter1.is_alive() && fighter2.is_alive() && rounds < max_rounds {
-            rounds += 1;
+        // Mana is spent on the attempt, whether or not it lands
+        let attack = attacker.choose_attack();
+        let outcome = self.resolve_attack(attacker, defender, &attack, turn);
+        spend_mana(attacker, attack.mana_cost);
+        Some(outcome)
+    }
+
+    fn resolve_attack<A, D>(
+        &mut self,
+        attacker: &A,
+        defender: &mut D,
+        attack: &AttackProfile,
+        turn: &mut TurnSummary,
+    ) -> AttackOutcome
+    where
+        A: Character,
+        D: Character,
+    {
+        match self.roll_attack(attacker, defender) {
+            Some(roll) => {
+                let hit = self.pipeline.calculate(attacker, defender, attack, roll);
+                let health_before = defender.get_health();
+                let landed = deal_damage(defender, hit.final_damage);
+                if landed == 0 {
+                    turn.blocked += 1;
+                }
+                turn.overkill += landed.saturating_sub(health_before);
+                AttackOutcome::Hit(hit)
+            }
+            None => {
+                turn.missed += 1;
+                AttackOutcome::Miss
+            }
+        }
+    }
+
+    fn use_item(&mut self, round: u32, user: &mut dyn Character, turn: &mut TurnSummary) -> bool {
+        let chosen = user
+            .get_inventory()
+            .and_then(|inventory| inventory.choose(user))
+            .map(|item| item.id.clone());
+        let Some(used) = chosen.and_then(|id| items::use_item(user, &id).ok()) else {
+            return false;
+        };
+        turn.items_used += 1;
+
+        let name = user.get_name().to_string();
+        self.emit(CombatEvent::ItemUsed {
+            round,
+            user: name.clone(),
+            item: used.item.clone(),
+        });
+        if used.healed > 0 {
+            self.emit(CombatEvent::Healed {
+                round: Some(round),
+                target: name.clone(),
+                amount: used.healed,
+                source: EffectSource::Action {
+                    actor: name.clone(),
+                    name: Some(used.item.clone()),
+                },
+                health_after: user.get_health(),
+            });
+        }
+        if used.mana_restored > 0 {
+            self.emit(CombatEvent::ManaRestored {
+                round: Some(round),
+                target: name.clone(),
+                amount: used.mana_restored,
+                mana_after: user.get_mana(),
+            });
+        }
+        for kind in used.statuses {
+            self.emit(CombatEvent::StatusApplied {
+                round,
+                target: name.clone(),
+                kind,
+            });
+        }
+        true
+    }
 
-            // Fighter 1 attacks Fighter 2
-            if fighter1.can_attack() {
-                let damage = attack(fighter1, fighter2);
-                println!(
-                    "Round {}: {} attacks {} for {} damage! ({} HP remaining)",
-                    rounds,
-                    fighter1.get_name(),
-                    fighter2.get_name(),
-                    damage,
-                    fighter2.get_health()
-                );
+    fn use_ability<A, D>(
+        &mut self,
+        round: u32,
+        user: &mut A,
+        enemy: &mut D,
+        ability: &Ability,
+        turn: &mut TurnSummary,
+    ) where
+        A: Character,
+        D: Character,
+    {
+        self.emit(CombatEvent::AbilityUsed {
+            round,
+            user: user.get_name().to_string(),
+            ability: ability.name.clone(),
+        });
+        spend_mana(user, ability.mana_cost);
+
+        for effect in &ability.effects {
+            match effect {
+                AbilityEffect::Strike {
+                    multiplier,
+                    bonus_damage,
+                    damage_type,
+                } => {
+                    let attack =
+                        ability.strike_profile(user, *multiplier, *bonus_damage, *damage_type);
+                    let outcome = self.resolve_attack(user, enemy, &attack, turn);
+                    self.report_strike(round, user, enemy, &outcome);
+                    if let AttackOutcome::Miss = outcome {
+                        return; // Follow-up effects ride on the strike landing
+                    }
+                }
+                AbilityEffect::Heal(amount) => {
+                    let actor = user.get_name().to_string();
+                    let target: &mut dyn Character = match ability.targeting {
+                        Targeting::OnSelf => user,
+                        Targeting::Enemy => enemy,
+                    };
+                    let health_before = target.get_health();
+                    heal(target, *amount);
+                    let event = CombatEvent::Healed {
+                        round: Some(round),
+                        target: target.get_name().to_string(),
+                        amount: target.get_health().saturating_sub(health_before),
+                        source: EffectSource::Action {
+                            actor,
+                            name: Some(ability.name.clone()),
+                        },
+                        health_after: target.get_health(),
+                    };
+                    self.emit(event);
+                }
+                AbilityEffect::ApplyStatus(status) => {
+                    let target: &mut dyn Character = match ability.targeting {
+                        Targeting::OnSelf => user,
+                        Targeting::Enemy => enemy,
+                    };
+                    target.apply_status(status.clone());
+                    let event = CombatEvent::StatusApplied {
+                        round,
+                        target: target.get_name().to_string(),
+                        kind: status.kind,
+                    };
+                    self.emit(event);
+                }
+                AbilityEffect::Taunt(amount) => {
+                    turn.taunt += amount;
+                    self.emit(CombatEvent::Taunted {
+                        round,
+                        user: user.get_name().to_string(),
+                        target: enemy.get_name().to_string(),
+                    });
+                }
             }
+        }
+    }
 
-            if !fighter2.is_alive() {
-                return BattleResult::Winner1;
+    fn report_strike(
+        &mut self,
+        round: u32,
+        attacker: &dyn Character,
+        defender: &dyn Character,
+        outcome: &AttackOutcome,
+    ) {
+        match outcome {
+            AttackOutcome::Hit(hit) => {
+                self.emit(CombatEvent::AttackPerformed {
+                    round,
+                    attacker: attacker.get_name().to_string(),
+                    defender: defender.get_name().to_string(),
+                    attack: hit.attack.clone(),
+                    damage: hit.final_damage,
+                    critical: hit.critical,
+                    health_after: defender.get_health(),
+                });
+                self.emit(CombatEvent::DamageTaken {
+                    round,
+                    target: defender.get_name().to_string(),
+                    amount: hit.final_damage,
+                    source: EffectSource::Action {
+                        actor: attacker.get_name().to_string(),
+                        name: hit.attack.clone(),
+                    },
+                    health_after: defender.get_health(),
+                });
             }
+            AttackOutcome::Miss => self.emit(CombatEvent::AttackMissed {
+                round,
+                attacker: attacker.get_name().to_string(),
+                defender: defender.get_name().to_string(),
+            }),
+        }
+    }
 
-            // Fighter 2 attacks Fighter 1
-            if fighter2.can_attack() {
-                let damage = attack(fighter2, fighter1);
-                println!(
-                    "Round {}: {} attacks {} for {} damage! ({} HP remaining)",
-                    rounds,
-                    fighter2.get_name(),
-                    fighter1.get_name(),
-                    damage,
-                    fighter1.get_health()
-                );
+    fn report_status_ticks(&mut self, round: u32, character: &dyn Character, ticks: &[StatusTick]) {
+        for tick in ticks {
+            let target = character.get_name().to_string();
+            let source = EffectSource::Status(tick.kind);
+            if tick.kind.is_harmful() {
+                self.emit(CombatEvent::DamageTaken {
+                    round,
+                    target,
+                    amount: tick.amount,
+                    source,
+                    health_after: tick.health_after,
+                });
+            } else {
+                self.emit(CombatEvent::Healed {
+                    round: Some(round),
+                    target,
+                    amount: tick.amount,
+                    source,
+                    health_after: tick.health_after,
+                });
             }
+        }
+    }
 
-            if !fighter1.is_alive() {
-                return BattleResult::Winner2;
+    fn report_defeat(&mut self, round: u32, character: &dyn Character, health_before: u32) {
+        if health_before > 0 && !character.is_alive() {
+            self.emit(CombatEvent::CharacterDefeated {
+                round,
+                name: character.get_name().to_string(),
+            });
+        }
+    }
+
+    pub fn take_turn<A, D>(&mut self, round: u32, attacker: &mut A, defender: &mut D) -> TurnSummary
+    where
+        A: Character,
+        D: Character,
+    {
+        if attacker.is_stunned() {
+            attacker
+                .get_status_effects_mut()
+                .consume_turn(StatusKind::Stun);
+            if attacker.can_attack() {
+                self.emit(CombatEvent::TurnSkipped {
+                    round,
+                    name: attacker.get_name().to_string(),
+                    status: StatusKind::Stun,
+                });
             }
+            return TurnSummary::default();
         }
 
-        if rounds >= max_rounds {
-            BattleResult::Draw
-        } else if fighter1.get_health() > fighter2.get_health() {
+        let attacker_health = attacker.get_health();
+        let defender_health = defender.get_health();
+        let mut turn = TurnSummary::default();
+
+        // Drinking a potion takes the whole turn; bystanders don't reach for theirs
+        if attacker.can_attack() && self.use_item(round, attacker, &mut turn) {
+            turn.healing_done = attacker.get_health().saturating_sub(attacker_health);
+            return turn;
+        }
+
+        let chosen = if attacker.can_attack() {
+            attacker.get_abilities().choose(attacker)
+        } else {
+            None
+        };
+        if let Some(ability) = chosen.and_then(|index| attacker.get_abilities_mut().trigger(index))
+        {
+            self.use_ability(round, attacker, defender, &ability, &mut turn);
+        } else if let Some(outcome) = self.strike(attacker, defender, &mut turn) {
+            self.report_strike(round, attacker, defender, &outcome);
+        }
+        self.report_defeat(round, defender, defender_health);
+
+        turn.damage_dealt = defender_health.saturating_sub(defender.get_health());
+        turn.healing_done = attacker.get_health().saturating_sub(attacker_health);
+        turn
+    }
+
+    // Speed decides who acts first; ties go to a random roll, or rotate each round
+    // when combat is deterministic so neither side always strikes first
+    pub fn turn_order(&mut self, round: u32, fighters: &[&dyn Character]) -> Vec<usize> {
+        if fighters.is_empty() {
+            return Vec::new();
+        }
+        let speeds: Vec<u32> = fighters.iter().map(|f| f.get_effective_speed()).collect();
+        let count = fighters.len();
+        let rotation = round.saturating_sub(1) as usize % count;
+        let tie_keys: Vec<u32> = match self.rng.as_mut() {
+            Some(rng) => (0..count).map(|_| rng.next_u32()).collect(),
+            None => (0..count)
+                .map(|index| ((index + count - rotation) % count) as u32)
+                .collect(),
+        };
+        initiative_order(&speeds, &tie_keys)
+    }
+
+    // Damage over time and regeneration resolve before anyone acts
+    pub fn start_round(&mut self, round: u32, character: &mut dyn Character) {
+        let health_before = character.get_health();
+        let ticks = tick_status_effects(character);
+        self.report_status_ticks(round, character, &ticks);
+        self.report_defeat(round, character, health_before);
+    }
+
+    pub fn end_round(&mut self, round: u32, character: &mut dyn Character) {
+        character.restore_mana(character.get_mana_regen());
+        character.get_abilities_mut().tick_cooldowns();
+        for kind in character.get_status_effects_mut().end_round() {
+            self.emit(CombatEvent::StatusExpired {
+                round,
+                target: character.get_name().to_string(),
+                kind,
+            });
+        }
+    }
+
+    // Effects wear off and cooldowns come back once a fight is over, so nothing carries
+    // into the next one
+    pub fn end_battle(&mut self, character: &mut dyn Character) {
+        character.get_status_effects_mut().clear();
+        character.get_abilities_mut().reset_cooldowns();
+    }
+
+    fn apply_sudden_death(&mut self, round: u32, character: &mut dyn Character) {
+        let damage = self.config.sudden_death.get_damage(round);
+        if damage == 0 || !character.is_alive() {
+            return;
+        }
+
+        let health_before = character.get_health();
+        character.set_health(health_before.saturating_sub(damage));
+        self.emit(CombatEvent::DamageTaken {
+            round,
+            target: character.get_name().to_string(),
+            amount: damage,
+            source: EffectSource::SuddenDeath,
+            health_after: character.get_health(),
+        });
+        self.report_defeat(round, character, health_before);
+    }
+
+    fn resolve_draw(&self, fighter1: &dyn Character, fighter2: &dyn Character) -> BattleResult {
+        let score = |fighter: &dyn Character| match self.config.draw_resolution {
+            DrawResolution::Draw => 0.0,
+            DrawResolution::HealthRemaining => fighter.get_health() as f32,
+            DrawResolution::HealthPercentage => {
+                fighter.get_health() as f32 / fighter.get_calculated_max_health().max(1) as f32
+            }
+        };
+        let (score1, score2) = (score(fighter1), score(fighter2));
+        if score1 > score2 {
             BattleResult::Winner1
-        } else if fighter2.get_health() > fighter1.get_health() {
+        } else if score2 > score1 {
             BattleResult::Winner2
         } else {
             BattleResult::Draw
         }
     }
+
+    pub fn battle<T1, T2>(&mut self, fighter1: &mut T1, fighter2: &mut T2) -> BattleResult
+    where
+        T1: Character,
+        T2: Character,
+    {
+        self.battle_with_report(fighter1, fighter2).result
+    }
+
+    pub fn battle_with_report<T1, T2>(
+        &mut self,
+        fighter1: &mut T1,
+        fighter2: &mut T2,
+    ) -> BattleReport
+    where
+        T1: Character,
+        T2: Character,
+    {
+        let mut fighters = [
+            FighterReport::new(fighter1.get_name()),
+            FighterReport::new(fighter2.get_name()),
+        ];
+        let mut fled = None;
+        let mut rounds = 0;
+        let max_rounds = self.config.max_rounds;
+
+        'rounds: while fighter1.is_alive() && fighter2.is_alive() && rounds < max_rounds {
+            rounds += 1;
+            self.emit(CombatEvent::RoundStarted { round: rounds });
+
+            let health = [fighter1.get_health(), fighter2.get_health()];
+            self.start_round(rounds, fighter1);
+            self.start_round(rounds, fighter2);
+            self.apply_sudden_death(rounds, fighter1);
+            self.apply_sudden_death(rounds, fighter2);
+            fighters[0].damage_taken += health[0].saturating_sub(fighter1.get_health());
+            fighters[1].damage_taken += health[1].saturating_sub(fighter2.get_health());
+
+            if !fighter1.is_alive() || !fighter2.is_alive() {
+                break;
+            }
+
+            let order = match self.config.first_strike {
+                FirstStrike::Initiative => self.turn_order(rounds, &[&*fighter1, &*fighter2]),
+                FirstStrike::Challenger => vec![0, 1],
+                FirstStrike::Defender => vec![1, 0],
+            };
+            for actor in order {
+                let turn = if actor == 0 {
+                    self.take_turn(rounds, fighter1, fighter2)
+                } else {
+                    self.take_turn(rounds, fighter2, fighter1)
+                };
+                fighters[actor].record_turn(&turn);
+                fighters[1 - actor].damage_taken += turn.damage_dealt;
+
+                if !fighter1.is_alive() || !fighter2.is_alive() {
+                    break 'rounds;
+                }
+
+                let cowards = [fighter1.wants_to_flee(), fighter2.wants_to_flee()];
+                if let Some(index) = cowards.iter().position(|&flees| flees) {
+                    self.emit(CombatEvent::Fled {
+                        round: rounds,
+                        name: fighters[index].name.clone(),
+                    });
+                    fled = Some(index);
+                    break 'rounds;
+                }
+            }
+
+            self.end_round(rounds, fighter1);
+            self.end_round(rounds, fighter2);
+        }
+
+        let (result, end_reason) = match (fled, fighter1.is_alive(), fighter2.is_alive()) {
+            (Some(0), _, _) => (BattleResult::Winner2, EndReason::Fled),
+            (Some(_), _, _) => (BattleResult::Winner1, EndReason::Fled),
+            (None, true, false) => (BattleResult::Winner1, EndReason::Knockout),
+            (None, false, true) => (BattleResult::Winner2, EndReason::Knockout),
+            (None, false, false) => (BattleResult::Draw, EndReason::Knockout),
+            (None, true, true) => (self.resolve_draw(fighter1, fighter2), EndReason::RoundLimit),
+        };
+        fighters[0].remaining_health = fighter1.get_health();
+        fighters[1].remaining_health = fighter2.get_health();
+
+        if self.config.auto_revive {
+            fighter1.revive();
+            fighter2.revive();
+        }
+        self.end_battle(fighter1);
+        self.end_battle(fighter2);
+
+        let outcome_event = match result {
+            BattleResult::Winner1 => CombatEvent::BattleWon {
+                winner: fighters[0].name.clone(),
+                loser: fighters[1].name.clone(),
+                rounds,
+            },
+            BattleResult::Winner2 => CombatEvent::BattleWon {
+                winner: fighters[1].name.clone(),
+                loser: fighters[0].name.clone(),
+                rounds,
+            },
+            BattleResult::Draw => CombatEvent::BattleDrawn { rounds },
+        };
+        self.emit(outcome_event);
+
+        BattleReport {
+            result,
+            end_reason,
+            rounds,
+            fighters,
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BattleResult {
     Winner1,
     Winner2,
     Draw,
 }
 
-pub fn heal<T: Character>(character: &mut T, amount: u32) {
-    let new_health = character.get_health() + amount;
+pub fn heal<T: Character + ?Sized>(character: &mut T, amount: u32) {
+    let new_health = character.get_health().saturating_add(amount);
     let max_health = character.get_calculated_max_health();
     character.set_health(new_health.min(max_health));
 }
@@ -205,15 +925,17 @@ where
     )
 }
 
-pub fn heal_party<T: Character>(party: &mut [T], amount: u32) {
+pub fn heal_party<T: Character>(party: &mut [T], amount: u32, observer: &mut dyn CombatObserver) {
     for character in party.iter_mut() {
+        let health_before = character.get_health();
         heal(character, amount);
-        println!(
-            "{} healed for {} HP! Current HP: {}",
-            character.get_name(),
-            amount,
-            character.get_health()
-        );
+        observer.on_event(&CombatEvent::Healed {
+            round: None,
+            target: character.get_name().to_string(),
+            amount: character.get_health().saturating_sub(health_before),
+            source: EffectSource::OutOfCombat,
+            health_after: character.get_health(),
+        });
     }
 }
 
@@ -221,12 +943,124 @@ pub fn party_total_health<T: Character>(party: &[T]) -> u32 {
     party.iter().map(|c| c.get_health()).sum()
 }
 
+#[allow(clippy::needless_lifetimes)]
 pub fn find_strongest<'a, T: Character>(characters: &'a [T]) -> Option<&'a T> {
     characters.iter().max_by_key(|c| c.get_calculated_damage())
 }
 
+#[allow(clippy::needless_lifetimes)]
 pub fn find_tankiest<'a, T: Character>(characters: &'a [T]) -> Option<&'a T> {
     characters
         .iter()
         .max_by_key(|c| c.get_calculated_max_health())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enemies::GoblinWarrior;
+    use crate::npcs::Merchant;
+    use crate::players::{PlayerMage, PlayerWarrior};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn seeded_battle(seed: u64) -> (BattleReport, Vec<CombatEvent>) {
+        let recorded = Rc::new(RefCell::new(Vec::new()));
+        let recorder = Rc::clone(&recorded);
+        let mut combat = CombatSystem::new()
+            .with_rng(StdRng::seed_from_u64(seed))
+            .with_observer(move |event: &CombatEvent| recorder.borrow_mut().push(event.clone()));
+        let mut hero = PlayerWarrior::new("Boromir");
+        let mut goblin = GoblinWarrior::new("Grishnakh");
+        let report = combat.battle_with_report(&mut hero, &mut goblin);
+        let events = recorded.borrow().clone();
+        (report, events)
+    }
+
+    #[test]
+    fn the_same_seed_replays_the_same_battle() {
+        let (first_report, first_events) = seeded_battle(7);
+        let (second_report, second_events) = seeded_battle(7);
+        assert_eq!(first_report, second_report);
+        assert_eq!(first_events, second_events);
+        assert!(!first_events.is_empty());
+    }
+
+    #[test]
+    fn effects_and_cooldowns_are_cleared_when_a_battle_ends() {
+        let mut combat = CombatSystem::new().with_rng(StdRng::seed_from_u64(3));
+        let mut hero = PlayerWarrior::new("Boromir");
+        let mut goblin = GoblinWarrior::new("Grishnakh");
+        hero.apply_status(StatusEffect::new(StatusKind::Regeneration, 1, 99));
+        goblin.apply_status(StatusEffect::new(StatusKind::Slow, 10, 99));
+        hero.get_abilities_mut().trigger(0);
+
+        combat.battle(&mut hero, &mut goblin);
+        for fighter in [&hero as &dyn Character, &goblin as &dyn Character] {
+            assert_eq!(fighter.get_status_effects().iter().count(), 0);
+            assert!(
+                fighter
+                    .get_abilities()
+                    .iter()
+                    .all(|(_, cooldown)| cooldown == 0)
+            );
+        }
+    }
+
+    #[test]
+    fn mages_fall_back_to_their_staff_without_mana() {
+        let mut mage = PlayerMage::new("Radagast");
+        assert_eq!(mage.choose_attack().name.as_deref(), Some("Arcane Bolt"));
+
+        mage.set_mana(5);
+        let fallback = mage.choose_attack();
+        assert_eq!(fallback.name.as_deref(), Some("Staff Strike"));
+        assert_eq!(fallback.mana_cost, 0);
+        assert_eq!(fallback.base_damage, mage.get_base_damage() / 3);
+    }
+
+    #[test]
+    fn spells_spend_mana_and_rounds_regenerate_it() {
+        let mut combat = CombatSystem::new().without_rng();
+        let mut mage = PlayerMage::new("Radagast");
+        let mut goblin = GoblinWarrior::new("Grishnakh");
+        *mage.get_abilities_mut() = Abilities::default();
+        let max_mana = mage.get_max_mana();
+
+        combat.take_turn(1, &mut mage, &mut goblin);
+        assert_eq!(mage.get_mana(), max_mana - 10);
+
+        combat.end_round(1, &mut mage);
+        let regen = mage.get_mana_regen();
+        assert!(regen > 0);
+        assert_eq!(mage.get_mana(), (max_mana - 10 + regen).min(max_mana));
+    }
+
+    #[test]
+    fn bystanders_dont_drink_potions() {
+        let mut combat = CombatSystem::new().without_rng();
+        let mut merchant = Merchant::new("Butterbur");
+        let mut hero = PlayerWarrior::new("Boromir");
+        merchant.inventory.add_by_id("health_potion", 1).unwrap();
+        let potions = merchant.inventory.count("health_potion");
+        merchant.set_health(1);
+
+        let turn = combat.take_turn(1, &mut merchant, &mut hero);
+        assert_eq!(turn.items_used, 0);
+        assert_eq!(merchant.inventory.count("health_potion"), potions);
+        assert_eq!(merchant.get_health(), 1);
+    }
+
+    #[test]
+    fn huge_amounts_saturate_instead_of_overflowing() {
+        let mut mage = PlayerMage::new("Radagast");
+        mage.earn_gold(u32::MAX);
+        mage.earn_gold(10);
+        assert_eq!(mage.get_gold(), u32::MAX);
+
+        mage.restore_mana(u32::MAX);
+        assert_eq!(mage.get_mana(), mage.get_max_mana());
+        heal(&mut mage, u32::MAX);
+        assert_eq!(mage.get_health(), mage.get_calculated_max_health());
+    }
+}
@@ -1,10 +1,36 @@
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum DamageType {
     Physical,
+    Slash,
+    Pierce,
+    Blunt,
     Magical,
+    Fire,
+    Frost,
+    Lightning,
+    Poison,
+    Holy,
+    Shadow,
 }
 
-#[derive(Debug, Clone)]
+impl DamageType {
+    pub fn is_physical(&self) -> bool {
+        matches!(
+            self,
+            DamageType::Physical | DamageType::Slash | DamageType::Pierce | DamageType::Blunt
+        )
+    }
+
+    pub fn category(&self) -> DamageType {
+        if self.is_physical() {
+            DamageType::Physical
+        } else {
+            DamageType::Magical
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AttackType {
     Melee,
     Ranged,
@@ -14,6 +40,29 @@ pub trait Class {
     fn get_base_health(&self) -> u32;
     fn get_damage_type(&self) -> DamageType;
     fn get_attack_type(&self) -> AttackType;
+    fn get_accuracy(&self) -> f32 {
+        0.9
+    }
+    fn get_evasion(&self) -> f32 {
+        0.05
+    }
+    fn get_crit_chance(&self) -> f32 {
+        0.05
+    }
+    fn get_armor(&self) -> u32 {
+        10
+    }
+    fn get_magic_resistance(&self) -> u32 {
+        10
+    }
+    fn get_speed(&self) -> u32 {
+        10
+    }
+}
+
+// Every point of strength adds 1% critical hit chance
+pub fn strength_crit_chance(strength: u32) -> f32 {
+    0.05 + strength as f32 * 0.01
 }
 
 #[derive(Debug, Clone)]
@@ -26,6 +75,10 @@ impl Warrior {
         Warrior { strength: 10 }
     }
 
+    pub fn with_strength(strength: u32) -> Self {
+        Warrior { strength }
+    }
+
     pub fn get_strength(&self) -> u32 {
         self.strength
     }
@@ -43,6 +96,22 @@ impl Class for Warrior {
     fn get_attack_type(&self) -> AttackType {
         AttackType::Melee
     }
+
+    fn get_accuracy(&self) -> f32 {
+        0.85
+    }
+
+    fn get_crit_chance(&self) -> f32 {
+        strength_crit_chance(self.strength)
+    }
+
+    fn get_armor(&self) -> u32 {
+        20
+    }
+
+    fn get_magic_resistance(&self) -> u32 {
+        5
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -59,6 +128,10 @@ impl Mage {
         }
     }
 
+    pub fn with_stats(mana: u32, power: u32) -> Self {
+        Mage { mana, power }
+    }
+
     pub fn get_mana(&self) -> u32 {
         self.mana
     }
@@ -80,6 +153,22 @@ impl Class for Mage {
     fn get_attack_type(&self) -> AttackType {
         AttackType::Ranged
     }
+
+    fn get_evasion(&self) -> f32 {
+        0.1
+    }
+
+    fn get_armor(&self) -> u32 {
+        5
+    }
+
+    fn get_magic_resistance(&self) -> u32 {
+        20
+    }
+
+    fn get_speed(&self) -> u32 {
+        12
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -110,6 +199,48 @@ impl ClassType {
         }
     }
 
+    pub fn get_accuracy(&self) -> f32 {
+        match self {
+            ClassType::Warrior(warrior) => warrior.get_accuracy(),
+            ClassType::Mage(mage) => mage.get_accuracy(),
+        }
+    }
+
+    pub fn get_evasion(&self) -> f32 {
+        match self {
+            ClassType::Warrior(warrior) => warrior.get_evasion(),
+            ClassType::Mage(mage) => mage.get_evasion(),
+        }
+    }
+
+    pub fn get_crit_chance(&self) -> f32 {
+        match self {
+            ClassType::Warrior(warrior) => warrior.get_crit_chance(),
+            ClassType::Mage(mage) => mage.get_crit_chance(),
+        }
+    }
+
+    pub fn get_armor(&self) -> u32 {
+        match self {
+            ClassType::Warrior(warrior) => warrior.get_armor(),
+            ClassType::Mage(mage) => mage.get_armor(),
+        }
+    }
+
+    pub fn get_magic_resistance(&self) -> u32 {
+        match self {
+            ClassType::Warrior(warrior) => warrior.get_magic_resistance(),
+            ClassType::Mage(mage) => mage.get_magic_resistance(),
+        }
+    }
+
+    pub fn get_speed(&self) -> u32 {
+        match self {
+            ClassType::Warrior(warrior) => warrior.get_speed(),
+            ClassType::Mage(mage) => mage.get_speed(),
+        }
+    }
+
     pub fn get_strength(&self) -> Option<u32> {
         match self {
             ClassType::Warrior(warrior) => Some(warrior.get_strength()),
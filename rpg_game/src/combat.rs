@@ -1,87 +1,209 @@
 use crate::character::{BattleResult, Character, CombatSystem};
+use crate::damage::{AttackRoll, DamagePipeline};
 use crate::enemies::Enemy;
+use crate::events::CombatEvent;
+use crate::party::{PartyBattleResult, PartyOutcome, PartyRules, Side};
 use crate::players::Player;
+use crate::quests::QuestLog;
+use crate::report::BattleReport;
+use crate::save::{SaveError, SaveSlots};
 
-pub struct Arena;
+#[derive(Debug, Clone)]
+pub struct TournamentResult<T> {
+    pub champion: T,
+    pub matches: Vec<BattleReport>, // In the order they were fought
+}
+
+#[derive(Default)]
+pub struct Arena {
+    combat: CombatSystem,
+    autosave: Option<(SaveSlots, u32)>,
+    quests: Option<QuestLog>,
+}
 
 impl Arena {
-    pub fn player_vs_enemy<P, E>(player: &mut P, enemy: &mut E) -> BattleResult
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_combat(combat: CombatSystem) -> Self {
+        Arena {
+            combat,
+            autosave: None,
+            quests: None,
+        }
+    }
+
+    // Saves the player to this slot after every arena fight; party fights save their leader
+    pub fn with_autosave(mut self, slots: SaveSlots, slot: u32) -> Self {
+        self.autosave = Some((slots, slot));
+        self
+    }
+
+    // Counts every fight its owner is in towards their quests
+    pub fn with_quests(mut self, log: QuestLog) -> Self {
+        self.quests = Some(log);
+        self
+    }
+
+    // Hands the quest log back, e.g. to claim rewards outside the arena
+    pub fn take_quests(&mut self) -> Option<QuestLog> {
+        self.quests.take()
+    }
+
+    // Grants experience and reports every level it unlocks
+    fn award_experience<P: Player>(&mut self, player: &mut P, amount: u32) {
+        let level_before = player.get_level();
+        player.add_experience(amount);
+        self.combat.emit(CombatEvent::ExperienceGained {
+            name: player.get_name().to_string(),
+            amount,
+        });
+        for level in level_before + 1..=player.get_level() {
+            self.combat.emit(CombatEvent::LevelUp {
+                name: player.get_name().to_string(),
+                level,
+            });
+        }
+    }
+
+    pub fn player_vs_enemy<P, E>(&mut self, player: &mut P, enemy: &mut E) -> BattleReport
     where
         P: Player + Character,
         E: Enemy + Character,
     {
-        println!("=== ARENA COMBAT ===");
-        println!(
-            "{} (Player, Level {}) vs {} (Enemy, Threat {})",
-            player.get_name(),
-            player.get_level(),
-            enemy.get_name(),
-            enemy.get_threat_level()
-        );
+        self.combat.emit(CombatEvent::ArenaMatch {
+            player: player.get_name().to_string(),
+            level: player.get_level(),
+            enemy: enemy.get_name().to_string(),
+            threat: enemy.get_threat_level(),
+        });
+
+        let report = self.combat.battle_with_report(player, enemy);
+
+        let won = report.result == BattleResult::Winner1;
+        if won {
+            self.award_experience(player, enemy.get_threat_level() * 25);
+        }
+        self.after_battle(&[&*player], &[&*enemy], won);
+
+        report
+    }
 
-        let result = CombatSystem::battle(player, enemy);
-
-        match result {
-            BattleResult::Winner1 => {
-                let exp_gained = enemy.get_threat_level() * 25;
-                player.add_experience(exp_gained);
-                println!(
-                    "{} wins and gains {} experience!",
-                    player.get_name(),
-                    exp_gained
-                );
+    pub fn party_vs_group<P, E>(
+        &mut self,
+        players: &mut [P],
+        enemies: &mut [E],
+        rules: &PartyRules,
+    ) -> PartyBattleResult
+    where
+        P: Player + Character,
+        E: Enemy + Character,
+    {
+        self.combat.emit(CombatEvent::PartyMatch {
+            players: players.len(),
+            enemies: enemies.len(),
+        });
+
+        let result = self.combat.party_battle(players, enemies, rules);
+
+        let won = result.outcome == PartyOutcome::Won(Side::Players);
+        if won {
+            // Experience from every enemy is shared among the survivors
+            let total_exp: u32 = enemies.iter().map(|e| e.get_threat_level() * 25).sum();
+            let exp_gained = total_exp / result.players_standing.max(1) as u32;
+            for player in players.iter_mut().filter(|p| p.is_alive()) {
+                self.award_experience(player, exp_gained);
             }
-            BattleResult::Winner2 => {
-                println!(
-                    "{} has been defeated by {}!",
-                    player.get_name(),
-                    enemy.get_name()
-                );
+        }
+        let players: Vec<&dyn Player> = players.iter().map(|p| p as &dyn Player).collect();
+        let enemies: Vec<&dyn Enemy> = enemies.iter().map(|e| e as &dyn Enemy).collect();
+        self.after_battle(&players, &enemies, won);
+
+        result
+    }
+
+    // Runs once a fight with players in it is over, whoever won. The first player
+    // is the one autosaved.
+    fn after_battle(&mut self, players: &[&dyn Player], enemies: &[&dyn Enemy], won: bool) {
+        if let Some(log) = &mut self.quests
+            && players.iter().any(|p| p.get_name() == log.get_owner())
+        {
+            let mut updates = Vec::new();
+            for enemy in enemies {
+                updates.extend(log.record_defeat(*enemy));
             }
-            BattleResult::Draw => {
-                println!("The battle ends in a draw!");
+            if won {
+                updates.extend(log.record_win());
+            }
+            for update in updates {
+                self.combat.emit(CombatEvent::QuestProgress {
+                    quest: update.quest,
+                    required: update.objective.get_required(),
+                    objective: update.objective.to_string(),
+                    progress: update.progress,
+                    completed: update.completed,
+                });
             }
         }
 
-        result
+        let Some(&leader) = players.first() else {
+            return;
+        };
+        if let Some((slots, slot)) = &self.autosave {
+            let saved = match leader.as_persist() {
+                Some(player) => slots.save(*slot, player),
+                None => Err(SaveError::NotPersistable(leader.get_name().to_string())),
+            };
+            let event = match saved {
+                Ok(()) => CombatEvent::GameSaved {
+                    name: leader.get_name().to_string(),
+                    slot: *slot,
+                },
+                Err(error) => CombatEvent::SaveFailed {
+                    slot: *slot,
+                    error: error.to_string(),
+                },
+            };
+            self.combat.emit(event);
+        }
     }
 
-    pub fn enemy_vs_enemy<E1, E2>(enemy1: &mut E1, enemy2: &mut E2) -> BattleResult
+    pub fn enemy_vs_enemy<E1, E2>(&mut self, enemy1: &mut E1, enemy2: &mut E2) -> BattleReport
     where
         E1: Enemy + Character,
         E2: Enemy + Character,
     {
-        println!("=== MONSTER BATTLE ===");
-        println!(
-            "{} (Threat {}) vs {} (Threat {})",
-            enemy1.get_name(),
-            enemy1.get_threat_level(),
-            enemy2.get_name(),
-            enemy2.get_threat_level()
-        );
+        self.combat.emit(CombatEvent::MonsterMatch {
+            first: enemy1.get_name().to_string(),
+            first_threat: enemy1.get_threat_level(),
+            second: enemy2.get_name().to_string(),
+            second_threat: enemy2.get_threat_level(),
+        });
 
-        CombatSystem::battle(enemy1, enemy2)
+        self.combat.battle_with_report(enemy1, enemy2)
     }
 
-    pub fn tournament<T>(mut participants: Vec<T>) -> Option<T>
+    pub fn tournament<T>(&mut self, mut participants: Vec<T>) -> Option<TournamentResult<T>>
     where
         T: Character + Clone,
     {
-        if participants.is_empty() {
-            return None;
+        if participants.len() <= 1 {
+            return participants.pop().map(|champion| TournamentResult {
+                champion,
+                matches: Vec::new(),
+            });
         }
 
-        if participants.len() == 1 {
-            return participants.into_iter().next();
-        }
-
-        println!("=== TOURNAMENT START ===");
-        println!("Participants: {}", participants.len());
+        self.combat.emit(CombatEvent::TournamentStarted {
+            participants: participants.len(),
+        });
 
+        let mut matches = Vec::new();
         let mut round = 1;
 
         while participants.len() > 1 {
-            println!("\n--- Round {} ---", round);
+            self.combat.emit(CombatEvent::TournamentRound { round });
             let mut next_round = Vec::new();
 
             // Pair up fighters
@@ -90,33 +212,31 @@ impl Arena {
                     let mut fighter1 = chunk[0].clone();
                     let mut fighter2 = chunk[1].clone();
 
-                    let result = CombatSystem::battle(&mut fighter1, &mut fighter2);
+                    let report = self.combat.battle_with_report(&mut fighter1, &mut fighter2);
 
-                    match result {
-                        BattleResult::Winner1 => {
-                            println!("{} advances to the next round!", fighter1.get_name());
-                            fighter1.revive(); // Heal for next round
-                            next_round.push(fighter1);
-                        }
-                        BattleResult::Winner2 => {
-                            println!("{} advances to the next round!", fighter2.get_name());
-                            fighter2.revive(); // Heal for next round
-                            next_round.push(fighter2);
-                        }
+                    // In case of draw, the one with higher health advances
+                    let mut winner = match report.result {
+                        BattleResult::Winner1 => fighter1,
+                        BattleResult::Winner2 => fighter2,
                         BattleResult::Draw => {
-                            // In case of draw, the one with higher health advances
                             if fighter1.get_health() >= fighter2.get_health() {
-                                fighter1.revive();
-                                next_round.push(fighter1);
+                                fighter1
                             } else {
-                                fighter2.revive();
-                                next_round.push(fighter2);
+                                fighter2
                             }
                         }
-                    }
+                    };
+                    self.combat.emit(CombatEvent::Advanced {
+                        name: winner.get_name().to_string(),
+                    });
+                    winner.revive(); // Heal for next round
+                    next_round.push(winner);
+                    matches.push(report);
                 } else {
                     // Odd number, last fighter gets a bye
-                    println!("{} gets a bye to the next round!", chunk[0].get_name());
+                    self.combat.emit(CombatEvent::Bye {
+                        name: chunk[0].get_name().to_string(),
+                    });
                     let mut bye_fighter = chunk[0].clone();
                     bye_fighter.revive();
                     next_round.push(bye_fighter);
@@ -127,7 +247,9 @@ impl Arena {
             round += 1;
         }
 
-        participants.into_iter().next()
+        participants
+            .pop()
+            .map(|champion| TournamentResult { champion, matches })
     }
 }
 
@@ -136,21 +258,105 @@ where
     A: Character,
     D: Character,
 {
-    let base_damage = attacker.get_calculated_damage();
-
-    // Type advantage system
-    let type_multiplier = match (attacker.get_damage_type(), defender.get_damage_type()) {
-        (crate::classes::DamageType::Physical, crate::classes::DamageType::Magical) => 1.2, // Physical vs Magical
-        (crate::classes::DamageType::Magical, crate::classes::DamageType::Physical) => 0.8, // Magical vs Physical
-        _ => 1.0, // Same types
-    };
-
-    // Attack type vs defense consideration
-    let attack_bonus = match (attacker.get_attack_type(), defender.get_attack_type()) {
-        (crate::classes::AttackType::Ranged, crate::classes::AttackType::Melee) => 1.15, // Ranged vs Melee
-        (crate::classes::AttackType::Melee, crate::classes::AttackType::Ranged) => 0.9, // Melee vs Ranged
-        _ => 1.0,
-    };
-
-    (base_damage as f32 * type_multiplier * attack_bonus) as u32
+    DamagePipeline::default()
+        .calculate(
+            attacker,
+            defender,
+            &attacker.get_attack(),
+            AttackRoll::default(),
+        )
+        .final_damage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actor::Preset;
+    use crate::enemies::GoblinWarrior;
+    use crate::players::PlayerWarrior;
+    use crate::quests::QuestStatus;
+    use crate::roster::Combatant;
+    use std::cell::RefCell;
+    use std::fs;
+    use std::rc::Rc;
+
+    fn scratch_slots(name: &str) -> SaveSlots {
+        let dir = std::env::temp_dir().join(format!("rpg_game_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        SaveSlots::new(dir)
+    }
+
+    #[test]
+    fn every_arena_fight_autosaves_the_player() {
+        let slots = scratch_slots("arena_autosave");
+        let mut arena =
+            Arena::with_combat(CombatSystem::new().without_rng()).with_autosave(slots.clone(), 1);
+        let mut hero = PlayerWarrior::new("Boromir");
+        let mut goblin = GoblinWarrior::new("Grishnakh");
+        arena.player_vs_enemy(&mut hero, &mut goblin);
+
+        let saved = slots.load(1).unwrap();
+        assert_eq!(saved.as_player().get_name(), "Boromir");
+        assert_eq!(saved.as_player().get_experience(), hero.get_experience());
+        let _ = fs::remove_dir_all(slots.path(1).parent().unwrap());
+    }
+
+    #[test]
+    fn party_fights_save_the_leader_if_it_can_be_saved() {
+        let slots = scratch_slots("party_autosave");
+        let recorded = Rc::new(RefCell::new(Vec::new()));
+        let recorder = Rc::clone(&recorded);
+        let combat = CombatSystem::new()
+            .without_rng()
+            .with_observer(move |event: &CombatEvent| recorder.borrow_mut().push(event.clone()));
+        let mut arena = Arena::with_combat(combat).with_autosave(slots.clone(), 2);
+
+        let mut party: Vec<Combatant> = vec![
+            PlayerWarrior::new("Boromir").into(),
+            PlayerWarrior::new("Faramir").into(),
+        ];
+        let mut goblins = vec![GoblinWarrior::new("Grishnakh")];
+        arena.party_vs_group(&mut party, &mut goblins, &PartyRules::default());
+        assert_eq!(slots.load(2).unwrap().as_player().get_name(), "Boromir");
+
+        let mut monsters: Vec<Combatant> = vec![GoblinWarrior::new("Ugluk").into()];
+        let mut goblins = vec![GoblinWarrior::new("Grishnakh")];
+        arena.party_vs_group(&mut monsters, &mut goblins, &PartyRules::default());
+        assert_eq!(
+            recorded.borrow().last(),
+            Some(&CombatEvent::SaveFailed {
+                slot: 2,
+                error: "Ugluk can't be saved".to_string(),
+            })
+        );
+        let _ = fs::remove_dir_all(slots.path(2).parent().unwrap());
+    }
+
+    #[test]
+    fn party_fights_count_towards_the_owners_quests() {
+        let mut log = QuestLog::new("Faramir");
+        log.accept(&Preset::QuestGiver.spawn("Elrond"), "goblin_menace")
+            .unwrap();
+        log.accept(&Preset::QuestGiver.spawn("Elrond"), "arena_champion")
+            .unwrap();
+        let mut arena = Arena::with_combat(CombatSystem::new().without_rng()).with_quests(log);
+
+        let mut party: Vec<Combatant> = vec![
+            PlayerWarrior::new("Boromir").into(),
+            PlayerWarrior::new("Faramir").into(),
+        ];
+        let mut goblins = vec![GoblinWarrior::new("Grishnakh"), GoblinWarrior::new("Ugluk")];
+        let result = arena.party_vs_group(&mut party, &mut goblins, &PartyRules::default());
+        assert_eq!(result.outcome, PartyOutcome::Won(Side::Players));
+
+        // Fights the owner sits out don't count
+        let mut aragorn = PlayerWarrior::new("Aragorn");
+        let mut goblin = GoblinWarrior::new("Lugdush");
+        arena.player_vs_enemy(&mut aragorn, &mut goblin);
+
+        let log = arena.take_quests().unwrap();
+        assert_eq!(log.get_status("goblin_menace"), Some(QuestStatus::Active));
+        let progress: Vec<_> = log.iter().map(|entry| entry.progress.clone()).collect();
+        assert_eq!(progress, vec![vec![2, 0], vec![1]]);
+    }
 }
@@ -0,0 +1,186 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FirstStrike {
+    Initiative, // Speed decides, with extra turns for much faster fighters
+    Challenger, // The first fighter always acts first, once per round
+    Defender,   // The second fighter always acts first, once per round
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SuddenDeath {
+    Off,
+    // From start_round on, both fighters lose damage HP per round, growing each round
+    Attrition { start_round: u32, damage: u32 },
+}
+
+impl SuddenDeath {
+    pub fn get_damage(&self, round: u32) -> u32 {
+        match self {
+            SuddenDeath::Off => 0,
+            SuddenDeath::Attrition {
+                start_round,
+                damage,
+            } if round >= *start_round => damage * (round - start_round + 1),
+            SuddenDeath::Attrition { .. } => 0,
+        }
+    }
+}
+
+// How a fight still going at the round limit is decided
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DrawResolution {
+    Draw,
+    HealthRemaining,
+    HealthPercentage,
+}
+
+#[derive(Debug, Clone)]
+pub struct BattleConfig {
+    pub max_rounds: u32,
+    pub first_strike: FirstStrike,
+    pub sudden_death: SuddenDeath,
+    pub draw_resolution: DrawResolution,
+    pub auto_revive: bool, // Knocked out fighters get back up once the battle is over
+}
+
+impl Default for BattleConfig {
+    fn default() -> Self {
+        BattleConfig {
+            max_rounds: 100,
+            first_strike: FirstStrike::Initiative,
+            sudden_death: SuddenDeath::Off,
+            draw_resolution: DrawResolution::Draw,
+            auto_revive: false,
+        }
+    }
+}
+
+impl BattleConfig {
+    // Short bouts that always produce a winner
+    pub fn arena() -> Self {
+        BattleConfig {
+            max_rounds: 30,
+            sudden_death: SuddenDeath::Attrition {
+                start_round: 20,
+                damage: 5,
+            },
+            draw_resolution: DrawResolution::HealthPercentage,
+            ..Self::default()
+        }
+    }
+
+    // The player's side opens every story encounter
+    pub fn story() -> Self {
+        BattleConfig {
+            first_strike: FirstStrike::Challenger,
+            draw_resolution: DrawResolution::HealthRemaining,
+            ..Self::default()
+        }
+    }
+
+    // Sparring against a dummy: nobody stays down
+    pub fn training() -> Self {
+        BattleConfig {
+            max_rounds: 10,
+            auto_revive: true,
+            ..Self::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::{BattleResult, Character, CombatSystem};
+    use crate::enemies::GoblinWarrior;
+    use crate::players::PlayerWarrior;
+    use crate::report::{BattleReport, EndReason};
+
+    fn fight(config: BattleConfig, hero: &mut PlayerWarrior) -> BattleReport {
+        let mut goblin = GoblinWarrior::new("Grishnakh");
+        CombatSystem::new()
+            .without_rng()
+            .with_config(config)
+            .battle_with_report(hero, &mut goblin)
+    }
+
+    #[test]
+    fn sudden_death_grows_each_round() {
+        let attrition = SuddenDeath::Attrition {
+            start_round: 3,
+            damage: 5,
+        };
+        assert_eq!(attrition.get_damage(2), 0);
+        assert_eq!(attrition.get_damage(3), 5);
+        assert_eq!(attrition.get_damage(5), 15);
+        assert_eq!(SuddenDeath::Off.get_damage(50), 0);
+    }
+
+    #[test]
+    fn the_defender_can_strike_first() {
+        let mut hero = PlayerWarrior::new("Boromir");
+        hero.set_health(1);
+        let config = BattleConfig {
+            first_strike: FirstStrike::Defender,
+            ..BattleConfig::default()
+        };
+
+        let report = fight(config, &mut hero);
+        assert_eq!(report.result, BattleResult::Winner2);
+        assert_eq!(report.rounds, 1);
+    }
+
+    #[test]
+    fn sudden_death_can_knock_out_both_fighters() {
+        let mut hero = PlayerWarrior::new("Boromir");
+        let config = BattleConfig {
+            sudden_death: SuddenDeath::Attrition {
+                start_round: 1,
+                damage: 1000,
+            },
+            ..BattleConfig::default()
+        };
+
+        let report = fight(config, &mut hero);
+        assert_eq!(report.result, BattleResult::Draw);
+        assert_eq!(report.end_reason, EndReason::Knockout);
+        assert_eq!(report.rounds, 1);
+    }
+
+    #[test]
+    fn draws_can_go_to_the_healthier_fighter() {
+        let wounded = || {
+            let mut hero = PlayerWarrior::new("Boromir");
+            hero.set_health(hero.get_calculated_max_health() / 2);
+            hero
+        };
+        let at_the_limit = |draw_resolution| BattleConfig {
+            max_rounds: 0,
+            draw_resolution,
+            ..BattleConfig::default()
+        };
+
+        let report = fight(at_the_limit(DrawResolution::Draw), &mut wounded());
+        assert_eq!(report.end_reason, EndReason::RoundLimit);
+        assert_eq!(report.result, BattleResult::Draw);
+        let report = fight(
+            at_the_limit(DrawResolution::HealthPercentage),
+            &mut wounded(),
+        );
+        assert_eq!(report.result, BattleResult::Winner2);
+    }
+
+    #[test]
+    fn training_fights_revive_the_loser() {
+        let mut hero = PlayerWarrior::new("Boromir");
+        hero.set_health(1);
+        let config = BattleConfig {
+            first_strike: FirstStrike::Defender,
+            ..BattleConfig::training()
+        };
+
+        let report = fight(config, &mut hero);
+        assert_eq!(report.result, BattleResult::Winner2);
+        assert_eq!(report.fighters[0].remaining_health, 0);
+        assert_eq!(hero.get_health(), hero.get_calculated_max_health());
+    }
+}
@@ -0,0 +1,470 @@
+use std::fmt;
+
+use crate::character::Character;
+use crate::classes::{AttackType, DamageType};
+use crate::elements::EffectivenessTable;
+
+// Random outcome of a single attack, rolled by the combat system before damage is calculated
+#[derive(Debug, Clone, Copy)]
+pub struct AttackRoll {
+    pub critical: bool,
+    pub variance: f32,
+}
+
+impl Default for AttackRoll {
+    fn default() -> Self {
+        AttackRoll {
+            critical: false,
+            variance: 1.0,
+        }
+    }
+}
+
+// What an attacker is swinging or casting this turn
+#[derive(Debug, Clone)]
+pub struct AttackProfile {
+    pub name: Option<String>, // None for a plain weapon attack
+    pub damage_type: DamageType,
+    pub attack_type: AttackType,
+    pub base_damage: u32,
+    pub mana_cost: u32,
+}
+
+pub struct DamageContext<'a> {
+    pub attacker: &'a dyn Character,
+    pub defender: &'a dyn Character,
+    pub attack: &'a AttackProfile,
+    pub roll: AttackRoll,
+}
+
+pub trait DamageStage {
+    fn name(&self) -> &str;
+    fn apply(&self, context: &DamageContext, damage: f32) -> f32;
+}
+
+pub struct CoefficientStage;
+
+impl DamageStage for CoefficientStage {
+    fn name(&self) -> &str {
+        "Character coefficient"
+    }
+
+    fn apply(&self, context: &DamageContext, damage: f32) -> f32 {
+        damage
+            * context
+                .attacker
+                .get_character_type()
+                .get_damage_coefficient()
+    }
+}
+
+pub struct TypeMatchupStage {
+    table: EffectivenessTable,
+}
+
+impl TypeMatchupStage {
+    pub fn new(table: EffectivenessTable) -> Self {
+        TypeMatchupStage { table }
+    }
+}
+
+impl DamageStage for TypeMatchupStage {
+    fn name(&self) -> &str {
+        "Damage type matchup"
+    }
+
+    fn apply(&self, context: &DamageContext, damage: f32) -> f32 {
+        damage
+            * self.table.multiplier(
+                context.attack.damage_type,
+                context.defender.get_damage_type(),
+            )
+    }
+}
+
+pub struct AffinityStage;
+
+impl DamageStage for AffinityStage {
+    fn name(&self) -> &str {
+        "Innate affinity"
+    }
+
+    fn apply(&self, context: &DamageContext, damage: f32) -> f32 {
+        damage
+            * context
+                .defender
+                .get_affinities()
+                .get_multiplier(context.attack.damage_type)
+    }
+}
+
+pub struct RangeMatchupStage;
+
+impl DamageStage for RangeMatchupStage {
+    fn name(&self) -> &str {
+        "Attack range matchup"
+    }
+
+    fn apply(&self, context: &DamageContext, damage: f32) -> f32 {
+        let multiplier = match (
+            context.attack.attack_type,
+            context.defender.get_attack_type(),
+        ) {
+            (AttackType::Ranged, AttackType::Melee) => 1.15, // Ranged vs Melee
+            (AttackType::Melee, AttackType::Ranged) => 0.9,  // Melee vs Ranged
+            _ => 1.0,
+        };
+        damage * multiplier
+    }
+}
+
+// Each point of defense removes a little less damage than the last: 100 defense halves damage
+pub fn defense_reduction(defense: u32) -> f32 {
+    100.0 / (100.0 + defense as f32)
+}
+
+pub struct DefenseStage;
+
+impl DamageStage for DefenseStage {
+    fn name(&self) -> &str {
+        "Defense mitigation"
+    }
+
+    fn apply(&self, context: &DamageContext, damage: f32) -> f32 {
+        damage * defense_reduction(context.defender.get_defense(context.attack.damage_type))
+    }
+}
+
+pub struct CriticalStage;
+
+impl DamageStage for CriticalStage {
+    fn name(&self) -> &str {
+        "Critical hit"
+    }
+
+    fn apply(&self, context: &DamageContext, damage: f32) -> f32 {
+        if context.roll.critical {
+            damage * context.attacker.get_crit_multiplier()
+        } else {
+            damage
+        }
+    }
+}
+
+pub struct VarianceStage;
+
+impl DamageStage for VarianceStage {
+    fn name(&self) -> &str {
+        "Damage variance"
+    }
+
+    fn apply(&self, context: &DamageContext, damage: f32) -> f32 {
+        damage * context.roll.variance
+    }
+}
+
+// Flat multiplier for balance tweaks and temporary buffs
+pub struct Multiplier {
+    name: String,
+    factor: f32,
+}
+
+impl Multiplier {
+    pub fn new(name: &str, factor: f32) -> Self {
+        Multiplier {
+            name: name.to_string(),
+            factor,
+        }
+    }
+}
+
+impl DamageStage for Multiplier {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn apply(&self, _context: &DamageContext, damage: f32) -> f32 {
+        damage * self.factor
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DamageStep {
+    pub stage: String,
+    pub before: f32,
+    pub after: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct DamageBreakdown {
+    pub attack: Option<String>,
+    pub mana_cost: u32,
+    pub base: u32,
+    pub critical: bool,
+    pub steps: Vec<DamageStep>,
+    pub final_damage: u32,
+}
+
+impl fmt::Display for DamageBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(attack) = &self.attack {
+            writeln!(f, "  Attack: {} ({} mana)", attack, self.mana_cost)?;
+        }
+        writeln!(f, "  Base damage: {}", self.base)?;
+        for step in &self.steps {
+            writeln!(
+                f,
+                "  {}: {:.2} -> {:.2}",
+                step.stage, step.before, step.after
+            )?;
+        }
+        write!(f, "  Final damage: {}", self.final_damage)?;
+        if self.critical {
+            write!(f, " (critical)")?;
+        }
+        Ok(())
+    }
+}
+
+// Base damage -> coefficients -> type/affinity/range matchup -> defense -> modifiers -> final
+pub struct DamagePipeline {
+    stages: Vec<Box<dyn DamageStage>>,
+}
+
+impl Default for DamagePipeline {
+    fn default() -> Self {
+        DamagePipeline {
+            stages: vec![
+                Box::new(CoefficientStage),
+                Box::new(TypeMatchupStage::new(EffectivenessTable::default())),
+                Box::new(AffinityStage),
+                Box::new(RangeMatchupStage),
+                Box::new(DefenseStage),
+                Box::new(CriticalStage),
+                Box::new(VarianceStage),
+            ],
+        }
+    }
+}
+
+impl DamagePipeline {
+    pub fn with_stage<S: DamageStage + 'static>(mut self, stage: S) -> Self {
+        self.stages.push(Box::new(stage));
+        self
+    }
+
+    pub fn calculate(
+        &self,
+        attacker: &dyn Character,
+        defender: &dyn Character,
+        attack: &AttackProfile,
+        roll: AttackRoll,
+    ) -> DamageBreakdown {
+        let context = DamageContext {
+            attacker,
+            defender,
+            attack,
+            roll,
+        };
+        let base = attack.base_damage;
+        let mut damage = base as f32;
+        let mut steps = Vec::with_capacity(self.stages.len());
+
+        for stage in &self.stages {
+            let after = stage.apply(&context, damage).max(0.0);
+            steps.push(DamageStep {
+                stage: stage.name().to_string(),
+                before: damage,
+                after,
+            });
+            damage = after;
+        }
+
+        DamageBreakdown {
+            attack: attack.name.clone(),
+            mana_cost: attack.mana_cost,
+            base,
+            critical: roll.critical,
+            steps,
+            final_damage: damage as u32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enemies::DragonBoss;
+    use crate::players::{PlayerMage, PlayerWarrior};
+
+    fn attack(damage_type: DamageType, base_damage: u32) -> AttackProfile {
+        AttackProfile {
+            name: None,
+            damage_type,
+            attack_type: AttackType::Melee,
+            base_damage,
+            mana_cost: 0,
+        }
+    }
+
+    fn only(stage: impl DamageStage + 'static) -> DamagePipeline {
+        DamagePipeline {
+            stages: vec![Box::new(stage)],
+        }
+    }
+
+    #[test]
+    fn defense_has_diminishing_returns() {
+        assert_eq!(defense_reduction(0), 1.0);
+        assert_eq!(defense_reduction(100), 0.5);
+        assert!(defense_reduction(300) > 0.0);
+    }
+
+    #[test]
+    fn defense_picks_armor_or_resistance_by_damage_type() {
+        let mage = PlayerMage::new("Gandalf");
+        let warrior = PlayerWarrior::new("Aragorn");
+        let pipeline = only(DefenseStage);
+        let roll = AttackRoll::default();
+
+        let physical = attack(DamageType::Physical, 40);
+        let expected = (40.0 * defense_reduction(warrior.get_armor())) as u32;
+        assert_eq!(expected, 33);
+        assert_eq!(
+            pipeline
+                .calculate(&mage, &warrior, &physical, roll)
+                .final_damage,
+            expected
+        );
+        let magical = attack(DamageType::Magical, 40);
+        let expected = (40.0 * defense_reduction(warrior.get_magic_resistance())) as u32;
+        assert_eq!(expected, 38);
+        assert_eq!(
+            pipeline
+                .calculate(&mage, &warrior, &magical, roll)
+                .final_damage,
+            expected
+        );
+    }
+
+    #[test]
+    fn every_stage_is_recorded_in_order() {
+        let warrior = PlayerWarrior::new("Aragorn");
+        let mage = PlayerMage::new("Gandalf");
+        let pipeline = DamagePipeline::default().with_stage(Multiplier::new("Buff", 2.0));
+
+        let breakdown = pipeline.calculate(
+            &warrior,
+            &mage,
+            &warrior.get_attack(),
+            AttackRoll::default(),
+        );
+        let first = breakdown.steps.first().unwrap();
+        let last = breakdown.steps.last().unwrap();
+        assert_eq!(first.stage, "Character coefficient");
+        assert_eq!(first.before, warrior.get_base_damage() as f32);
+        assert_eq!(last.stage, "Buff");
+        assert_eq!(last.after, last.before * 2.0);
+        for pair in breakdown.steps.windows(2) {
+            assert_eq!(pair[0].after, pair[1].before);
+        }
+        assert_eq!(breakdown.final_damage, last.after as u32);
+    }
+
+    #[test]
+    fn critical_hits_use_the_attackers_multiplier() {
+        let warrior = PlayerWarrior::new("Aragorn");
+        let mage = PlayerMage::new("Gandalf");
+        let pipeline = only(CriticalStage);
+        let slash = attack(DamageType::Physical, 20);
+
+        let normal = pipeline.calculate(&warrior, &mage, &slash, AttackRoll::default());
+        let critical = pipeline.calculate(
+            &warrior,
+            &mage,
+            &slash,
+            AttackRoll {
+                critical: true,
+                variance: 1.0,
+            },
+        );
+        assert_eq!(normal.final_damage, 20);
+        assert!(!normal.critical);
+        assert_eq!(critical.final_damage, 30);
+        assert!(critical.critical);
+    }
+
+    #[test]
+    fn variance_scales_the_final_damage() {
+        let warrior = PlayerWarrior::new("Aragorn");
+        let mage = PlayerMage::new("Gandalf");
+        let pipeline = only(VarianceStage);
+        let slash = attack(DamageType::Physical, 100);
+
+        let low = AttackRoll {
+            critical: false,
+            variance: 0.9,
+        };
+        let high = AttackRoll {
+            critical: false,
+            variance: 1.1,
+        };
+        assert_eq!(
+            pipeline
+                .calculate(&warrior, &mage, &slash, low)
+                .final_damage,
+            90
+        );
+        assert_eq!(
+            pipeline
+                .calculate(&warrior, &mage, &slash, high)
+                .final_damage,
+            110
+        );
+    }
+
+    #[test]
+    fn ranged_attackers_punish_melee_defenders() {
+        let warrior = PlayerWarrior::new("Aragorn");
+        let mage = PlayerMage::new("Gandalf");
+        let pipeline = only(RangeMatchupStage);
+        let mut bolt = attack(DamageType::Magical, 100);
+        bolt.attack_type = AttackType::Ranged;
+
+        let roll = AttackRoll::default();
+        let ranged = pipeline.calculate(&mage, &warrior, &bolt, roll);
+        assert_eq!(ranged.final_damage, 115);
+        let melee = pipeline.calculate(&warrior, &mage, &attack(DamageType::Physical, 100), roll);
+        assert_eq!(melee.final_damage, 90);
+    }
+
+    #[test]
+    fn immune_defenders_take_nothing() {
+        let mage = PlayerMage::new("Gandalf");
+        let dragon = DragonBoss::new("Smaug");
+
+        let breakdown = DamagePipeline::default().calculate(
+            &mage,
+            &dragon,
+            &attack(DamageType::Fire, 50),
+            AttackRoll::default(),
+        );
+        assert_eq!(breakdown.final_damage, 0);
+    }
+
+    #[test]
+    fn damage_never_goes_negative() {
+        let warrior = PlayerWarrior::new("Aragorn");
+        let mage = PlayerMage::new("Gandalf");
+        let pipeline = only(Multiplier::new("Curse", -3.0));
+
+        let breakdown = pipeline.calculate(
+            &warrior,
+            &mage,
+            &attack(DamageType::Physical, 10),
+            AttackRoll::default(),
+        );
+        assert_eq!(breakdown.steps[0].after, 0.0);
+        assert_eq!(breakdown.final_damage, 0);
+    }
+}
@@ -0,0 +1,228 @@
+use std::fmt;
+
+use crate::actor::{Actor, ActorTemplate};
+use crate::items::{Inventory, STOCK_MAX_WEIGHT, STOCK_SLOTS, item_by_id};
+use crate::trade::{Shop, TradeError};
+
+pub const DAYS_PER_WEEK: u32 = 7;
+const CUSTOMER_GOLD: u32 = 1_000_000; // Simulated customers never run dry
+
+// A trade a customer makes every simulated day
+#[derive(Debug, Clone)]
+pub struct Order {
+    pub customer: String,
+    pub item: String,
+    pub quantity: u32,
+    pub selling: bool, // Sellers bring the goods with them
+}
+
+impl Order {
+    pub fn buy(customer: &str, item: &str, quantity: u32) -> Self {
+        Order {
+            customer: customer.to_string(),
+            item: item.to_string(),
+            quantity,
+            selling: false,
+        }
+    }
+
+    pub fn sell(customer: &str, item: &str, quantity: u32) -> Self {
+        Order {
+            selling: true,
+            ..Self::buy(customer, item, quantity)
+        }
+    }
+}
+
+// The market at closing time
+#[derive(Debug, Clone)]
+pub struct MarketDay {
+    pub day: u32,
+    pub prices: Vec<u32>, // What each tracked item's buyer was quoted, in MarketReport::items order
+    pub stock: Vec<u32>,
+    pub trades: u32,
+    pub refusals: Vec<TradeError>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MarketReport {
+    pub items: Vec<String>,
+    pub days: Vec<MarketDay>,
+}
+
+impl MarketReport {
+    pub fn price_curve(&self, id: &str) -> Option<Vec<u32>> {
+        let index = self.items.iter().position(|item| item == id)?;
+        Some(self.days.iter().map(|day| day.prices[index]).collect())
+    }
+}
+
+impl fmt::Display for MarketReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:<24}", "price (stock)")?;
+        for day in &self.days {
+            write!(f, "{:>10}", format!("day {}", day.day))?;
+        }
+        writeln!(f)?;
+        for (index, item) in self.items.iter().enumerate() {
+            write!(f, "{:<24}", item)?;
+            for day in &self.days {
+                let cell = format!("{} ({})", day.prices[index], day.stock[index]);
+                write!(f, "{:>10}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "{:<24}", "trades / refused")?;
+        for day in &self.days {
+            let cell = format!("{} / {}", day.trades, day.refusals.len());
+            write!(f, "{:>10}", cell)?;
+        }
+        writeln!(f)
+    }
+}
+
+fn customer(name: &str) -> Actor {
+    ActorTemplate {
+        gold: CUSTOMER_GOLD,
+        passive: true,
+        ..ActorTemplate::new("customer", name)
+    }
+    .spawn()
+}
+
+// Runs the same orders every day with nothing printed, so price curves can be
+// compared between tunings. Each day after the first starts with a restock.
+pub fn simulate(
+    shop: &mut Shop,
+    merchant: &mut Actor,
+    orders: &[Order],
+    days: u32,
+) -> MarketReport {
+    let mut items: Vec<String> = Vec::new();
+    let ids = merchant.template.items.iter().map(|(id, _)| id);
+    for id in ids.chain(orders.iter().map(|order| &order.item)) {
+        if !items.contains(id) && item_by_id(id).is_some() {
+            items.push(id.clone());
+        }
+    }
+
+    let mut customers: Vec<Actor> = Vec::new();
+    let mut report = MarketReport {
+        items,
+        days: Vec::new(),
+    };
+    for index in 0..days {
+        if index > 0 {
+            shop.pass_days(merchant, 1);
+        }
+
+        let mut trades = 0;
+        let mut refusals = Vec::new();
+        for order in orders {
+            let position = match customers
+                .iter()
+                .position(|customer| customer.name == order.customer)
+            {
+                Some(position) => position,
+                None => {
+                    customers.push(customer(&order.customer));
+                    customers.len() - 1
+                }
+            };
+            // Whatever was bought before has gone home with the customer
+            let buyer = &mut customers[position];
+            buyer.inventory = Inventory::new(STOCK_SLOTS, STOCK_MAX_WEIGHT);
+            let result = if order.selling {
+                let _ = buyer.inventory.add_by_id(&order.item, order.quantity);
+                shop.sell(merchant, buyer, &order.item, order.quantity)
+            } else {
+                shop.buy(merchant, buyer, &order.item, order.quantity)
+            };
+            match result {
+                Ok(_) => trades += 1,
+                Err(error) => refusals.push(error),
+            }
+        }
+
+        let items: Vec<_> = report
+            .items
+            .iter()
+            .filter_map(|id| item_by_id(id))
+            .collect();
+        // Quoted to the first customer who buys the item, or to a stranger walking in
+        let prices = items
+            .iter()
+            .map(|item| {
+                let buyer = orders
+                    .iter()
+                    .find(|order| !order.selling && order.item == item.id)
+                    .map_or("", |order| order.customer.as_str());
+                shop.quote(item, merchant, buyer)
+            })
+            .collect();
+        report.days.push(MarketDay {
+            day: shop.get_day(),
+            prices,
+            stock: items
+                .iter()
+                .map(|item| merchant.inventory.count(&item.id))
+                .collect(),
+            trades,
+            refusals,
+        });
+    }
+    report
+}
+
+pub fn simulate_week(shop: &mut Shop, merchant: &mut Actor, orders: &[Order]) -> MarketReport {
+    simulate(shop, merchant, orders, DAYS_PER_WEEK)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::npcs::{LegendaryNPC, Merchant};
+
+    #[test]
+    fn buying_raises_prices_and_restocking_refills_the_stall() {
+        let mut merchant = Merchant::new("Butterbur");
+        let stocked = merchant.inventory.count("health_potion");
+        let orders = [Order::buy("Adventurers", "health_potion", 4)];
+
+        let report = simulate(&mut Shop::new(), &mut merchant, &orders, 3);
+        let curve = report.price_curve("health_potion").unwrap();
+        let potion = item_by_id("health_potion").unwrap();
+        assert!(curve[0] > potion.value);
+        assert!(curve.windows(2).all(|pair| pair[1] >= pair[0]));
+        assert!(report.days[0].stock[0] < stocked);
+        assert_eq!(report.days.iter().map(|day| day.trades).sum::<u32>(), 3);
+    }
+
+    #[test]
+    fn prices_are_what_the_buyer_was_quoted() {
+        let mut sage = LegendaryNPC::new("Elrond");
+        let mut shop = Shop::new().with_reputation("Regular", 10);
+        let orders = [Order::buy("Regular", "health_potion", 1)];
+
+        let report = simulate(&mut shop, &mut sage, &orders, 1);
+        let potion = item_by_id("health_potion").unwrap();
+        let price = report.price_curve("health_potion").unwrap()[0];
+        assert_eq!(price, shop.quote(&potion, &sage, "Regular"));
+        assert_ne!(price, shop.sell_price(&potion));
+    }
+
+    #[test]
+    fn refused_orders_are_reported() {
+        let mut merchant = Merchant::new("Butterbur");
+        let orders = [Order::buy("Hoarder", "health_potion", 999)];
+
+        let report = simulate(&mut Shop::new(), &mut merchant, &orders, 2);
+        for day in &report.days {
+            assert_eq!(day.trades, 0);
+            assert!(matches!(
+                day.refusals.as_slice(),
+                [TradeError::OutOfStock { .. }]
+            ));
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use crate::classes::DamageType;
+
+// (attacking type, defending type, multiplier). Pairs missing here fall back to the
+// Physical/Magical category of both types.
+const STANDARD_MATCHUPS: &[(DamageType, DamageType, f32)] = &[
+    (DamageType::Physical, DamageType::Magical, 1.2),
+    (DamageType::Magical, DamageType::Physical, 0.8),
+    (DamageType::Fire, DamageType::Frost, 1.5),
+    (DamageType::Fire, DamageType::Poison, 1.25),
+    (DamageType::Fire, DamageType::Fire, 0.5),
+    (DamageType::Frost, DamageType::Fire, 1.5),
+    (DamageType::Frost, DamageType::Lightning, 0.75),
+    (DamageType::Frost, DamageType::Frost, 0.5),
+    (DamageType::Lightning, DamageType::Frost, 1.25),
+    (DamageType::Lightning, DamageType::Lightning, 0.5),
+    (DamageType::Poison, DamageType::Holy, 0.5),
+    (DamageType::Poison, DamageType::Poison, 0.0),
+    (DamageType::Holy, DamageType::Shadow, 1.75),
+    (DamageType::Holy, DamageType::Holy, 0.5),
+    (DamageType::Shadow, DamageType::Holy, 1.5),
+    (DamageType::Shadow, DamageType::Shadow, 0.5),
+    (DamageType::Blunt, DamageType::Frost, 1.25),
+    (DamageType::Pierce, DamageType::Lightning, 0.9),
+];
+
+#[derive(Debug, Clone)]
+pub struct EffectivenessTable {
+    matchups: HashMap<(DamageType, DamageType), f32>,
+}
+
+impl Default for EffectivenessTable {
+    fn default() -> Self {
+        let table = EffectivenessTable {
+            matchups: HashMap::new(),
+        };
+        STANDARD_MATCHUPS
+            .iter()
+            .fold(table, |table, &(attack, defend, multiplier)| {
+                table.with_matchup(attack, defend, multiplier)
+            })
+    }
+}
+
+impl EffectivenessTable {
+    pub fn with_matchup(mut self, attack: DamageType, defend: DamageType, multiplier: f32) -> Self {
+        self.matchups.insert((attack, defend), multiplier);
+        self
+    }
+
+    pub fn multiplier(&self, attack: DamageType, defend: DamageType) -> f32 {
+        self.matchups
+            .get(&(attack, defend))
+            .or_else(|| self.matchups.get(&(attack.category(), defend.category())))
+            .copied()
+            .unwrap_or(1.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Affinity {
+    Weak,      // 1.5x damage taken
+    Resistant, // 0.5x damage taken
+    Immune,    // no damage taken
+}
+
+impl Affinity {
+    pub fn get_multiplier(&self) -> f32 {
+        match self {
+            Affinity::Weak => 1.5,
+            Affinity::Resistant => 0.5,
+            Affinity::Immune => 0.0,
+        }
+    }
+}
+
+// Innate weaknesses and immunities a character has regardless of its own damage type
+#[derive(Debug, Clone, Default)]
+pub struct Affinities {
+    affinities: HashMap<DamageType, Affinity>,
+}
+
+impl Affinities {
+    pub fn with(mut self, damage_type: DamageType, affinity: Affinity) -> Self {
+        self.affinities.insert(damage_type, affinity);
+        self
+    }
+
+    pub fn get(&self, damage_type: DamageType) -> Option<Affinity> {
+        self.affinities.get(&damage_type).copied()
+    }
+
+    pub fn get_multiplier(&self, damage_type: DamageType) -> f32 {
+        self.get(damage_type)
+            .map_or(1.0, |affinity| affinity.get_multiplier())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listed_matchups_win_over_categories() {
+        let table = EffectivenessTable::default();
+        assert_eq!(table.multiplier(DamageType::Fire, DamageType::Frost), 1.5);
+        assert_eq!(
+            table.multiplier(DamageType::Poison, DamageType::Poison),
+            0.0
+        );
+        assert_eq!(table.multiplier(DamageType::Blunt, DamageType::Frost), 1.25);
+    }
+
+    #[test]
+    fn missing_pairs_fall_back_to_their_categories() {
+        let table = EffectivenessTable::default();
+        assert_eq!(table.multiplier(DamageType::Slash, DamageType::Holy), 1.2);
+        assert_eq!(
+            table.multiplier(DamageType::Lightning, DamageType::Blunt),
+            0.8
+        );
+        assert_eq!(table.multiplier(DamageType::Slash, DamageType::Pierce), 1.0);
+        assert_eq!(table.multiplier(DamageType::Holy, DamageType::Fire), 1.0);
+    }
+
+    #[test]
+    fn custom_matchups_override_the_standard_ones() {
+        let table = EffectivenessTable::default()
+            .with_matchup(DamageType::Fire, DamageType::Frost, 3.0)
+            .with_matchup(DamageType::Slash, DamageType::Pierce, 0.9);
+        assert_eq!(table.multiplier(DamageType::Fire, DamageType::Frost), 3.0);
+        assert_eq!(table.multiplier(DamageType::Slash, DamageType::Pierce), 0.9);
+        assert_eq!(table.multiplier(DamageType::Frost, DamageType::Fire), 1.5);
+    }
+
+    #[test]
+    fn affinities_only_change_their_own_type() {
+        let affinities = Affinities::default()
+            .with(DamageType::Fire, Affinity::Weak)
+            .with(DamageType::Frost, Affinity::Resistant)
+            .with(DamageType::Poison, Affinity::Immune);
+        assert_eq!(affinities.get_multiplier(DamageType::Fire), 1.5);
+        assert_eq!(affinities.get_multiplier(DamageType::Frost), 0.5);
+        assert_eq!(affinities.get_multiplier(DamageType::Poison), 0.0);
+        assert_eq!(affinities.get(DamageType::Magical), None);
+        assert_eq!(affinities.get_multiplier(DamageType::Magical), 1.0);
+    }
+
+    #[test]
+    fn a_later_affinity_replaces_an_earlier_one() {
+        let affinities = Affinities::default()
+            .with(DamageType::Holy, Affinity::Weak)
+            .with(DamageType::Holy, Affinity::Immune);
+        assert_eq!(affinities.get(DamageType::Holy), Some(Affinity::Immune));
+    }
+}
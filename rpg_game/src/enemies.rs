@@ -1,284 +1,117 @@
+use crate::actor::{Actor, ActorTemplate, Preset};
 use crate::character::{Character, CharacterType};
 use crate::classes::{AttackType, ClassType, DamageType, Mage, Warrior};
+use crate::elements::Affinity;
+use crate::threat::ThreatTable;
 
 pub trait Enemy: Character {
+    fn get_id(&self) -> &str; // The template ID quests count defeats by
     fn get_aggro(&self) -> u32;
     fn get_threat_level(&self) -> u32;
+    fn get_threat_table(&self) -> &ThreatTable;
+    fn get_threat_table_mut(&mut self) -> &mut ThreatTable;
 }
 
-#[derive(Debug, Clone)]
-pub struct GoblinWarrior {
-    pub name: String,
-    pub health: u32,
-    pub max_health: u32,
-    pub strength: u32,
-    pub class: ClassType,
-    pub character_type: CharacterType,
-}
-
-impl Character for GoblinWarrior {
-    fn new(name: &str) -> Self {
-        let character_type = CharacterType::Enemy { threat_level: 3 };
-        let class = ClassType::Warrior(Warrior::new());
-        let base_health = 50;
-        let max_health = (base_health as f32 * character_type.get_health_coefficient()) as u32;
-
-        GoblinWarrior {
-            name: name.to_string(),
-            health: max_health,
-            max_health,
-            strength: 5,
-            class,
-            character_type,
-        }
-    }
-
-    fn revive(&mut self) {
-        if self.health == 0 {
-            self.health = self.get_calculated_max_health();
-        }
-    }
+// The enemy types from before templates, kept as constructors so
+// `GoblinWarrior::new(name)` still spawns the preset
+pub struct GoblinWarrior;
+pub struct GoblinMage;
+pub struct DragonBoss;
 
-    fn get_name(&self) -> &str {
-        &self.name
-    }
-
-    fn get_health(&self) -> u32 {
-        self.health
-    }
-
-    fn set_health(&mut self, health: u32) {
-        self.health = health.min(self.max_health);
-    }
-
-    fn get_class_type(&self) -> &ClassType {
-        &self.class
-    }
-
-    fn get_character_type(&self) -> &CharacterType {
-        &self.character_type
-    }
-
-    fn get_base_health(&self) -> u32 {
-        50
-    }
-
-    fn get_damage_type(&self) -> DamageType {
-        DamageType::Physical
-    }
-
-    fn get_attack_type(&self) -> AttackType {
-        AttackType::Melee
-    }
-
-    fn get_base_damage(&self) -> u32 {
-        15
+#[allow(clippy::new_ret_no_self)]
+impl GoblinWarrior {
+    pub fn new(name: &str) -> Actor {
+        Preset::GoblinWarrior.spawn(name)
     }
 }
 
-impl Enemy for GoblinWarrior {
-    fn get_aggro(&self) -> u32 {
-        75
-    }
-
-    fn get_threat_level(&self) -> u32 {
-        if let CharacterType::Enemy { threat_level } = &self.character_type {
-            *threat_level
-        } else {
-            1
-        }
+#[allow(clippy::new_ret_no_self)]
+impl GoblinMage {
+    pub fn new(name: &str) -> Actor {
+        Preset::GoblinMage.spawn(name)
     }
 }
 
-impl GoblinWarrior {
-    pub fn get_strength(&self) -> u32 {
-        self.strength
-    }
-
-    pub fn berserker_rage(&mut self) -> u32 {
-        // Use strength for special ability
-        let bonus_damage = self.strength * 2;
-        println!(
-            "{} enters berserker rage! Bonus damage: {}",
-            self.name, bonus_damage
-        );
-        bonus_damage
+#[allow(clippy::new_ret_no_self)]
+impl DragonBoss {
+    pub fn new(name: &str) -> Actor {
+        Preset::DragonBoss.spawn(name)
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct GoblinMage {
-    pub name: String,
-    pub health: u32,
-    pub max_health: u32,
-    pub power: u32,
-    pub mana: u32,
-    pub class: ClassType,
-    pub character_type: CharacterType,
-}
-
-impl Character for GoblinMage {
-    fn new(name: &str) -> Self {
-        let character_type = CharacterType::Enemy { threat_level: 4 };
-        let class = ClassType::Mage(Mage::new());
-        let base_health = 25;
-        let max_health = (base_health as f32 * character_type.get_health_coefficient()) as u32;
-
-        GoblinMage {
-            name: name.to_string(),
-            health: max_health,
-            max_health,
-            power: 5,
-            mana: 50,
-            class,
-            character_type,
-        }
-    }
-
-    fn revive(&mut self) {
-        if self.health == 0 {
-            self.health = self.get_calculated_max_health();
-            self.mana = 50;
-        }
-    }
-
-    fn get_name(&self) -> &str {
-        &self.name
-    }
-
-    fn get_health(&self) -> u32 {
-        self.health
-    }
-
-    fn set_health(&mut self, health: u32) {
-        self.health = health.min(self.max_health);
-    }
-
-    fn get_class_type(&self) -> &ClassType {
-        &self.class
-    }
-
-    fn get_character_type(&self) -> &CharacterType {
-        &self.character_type
-    }
-
-    fn get_base_health(&self) -> u32 {
-        25
-    }
-
-    fn get_damage_type(&self) -> DamageType {
-        DamageType::Magical
-    }
-
-    fn get_attack_type(&self) -> AttackType {
-        AttackType::Ranged
-    }
-
-    fn get_base_damage(&self) -> u32 {
-        20
-    }
-
-    fn can_attack(&self) -> bool {
-        self.get_health() > 0 && self.mana >= 10
+// Goblins fight with 5 strength or power rather than the class default of 10: a 10%
+// crit chance and a 10 damage Berserker Rage, or a 20 damage bolt and 2 mana per round
+pub fn goblin_warrior() -> ActorTemplate {
+    ActorTemplate {
+        character_type: CharacterType::Enemy { threat_level: 3 },
+        class: ClassType::Warrior(Warrior::with_strength(5)),
+        base_health: 50,
+        base_damage: 15,
+        armor: Some(10),
+        magic_resistance: Some(0),
+        speed: Some(11),
+        aggro: 75,
+        abilities: vec!["berserker_rage".to_string()],
+        ..ActorTemplate::new("goblin_warrior", "Goblin Warrior")
     }
 }
 
-impl Enemy for GoblinMage {
-    fn get_aggro(&self) -> u32 {
-        25
-    }
-
-    fn get_threat_level(&self) -> u32 {
-        if let CharacterType::Enemy { threat_level } = &self.character_type {
-            *threat_level
-        } else {
-            1
-        }
+pub fn goblin_mage() -> ActorTemplate {
+    let class = Mage::with_stats(100, 5);
+    ActorTemplate {
+        character_type: CharacterType::Enemy { threat_level: 4 },
+        mana: Some(class.get_mana() / 2), // Goblins channel half a mage's pool
+        class: ClassType::Mage(class),
+        base_health: 25,
+        base_damage: 20,
+        damage_type: DamageType::Magical,
+        attack_type: AttackType::Ranged,
+        attack_cost: 10,
+        armor: Some(0),
+        magic_resistance: Some(15),
+        speed: Some(9),
+        aggro: 25,
+        flee_threshold: 0.25, // Cowardly casters bolt once the fight turns against them
+        ..ActorTemplate::new("goblin_mage", "Goblin Mage")
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct DragonBoss {
-    pub name: String,
-    pub health: u32,
-    pub max_health: u32,
-    pub class: ClassType,
-    pub character_type: CharacterType,
-}
-
-impl Character for DragonBoss {
-    fn new(name: &str) -> Self {
-        let character_type = CharacterType::Enemy { threat_level: 15 };
-        let class = ClassType::Warrior(Warrior::new());
-        let base_health = 200;
-        let max_health = (base_health as f32 * character_type.get_health_coefficient()) as u32;
-
-        DragonBoss {
-            name: name.to_string(),
-            health: max_health,
-            max_health,
-            class,
-            character_type,
-        }
-    }
-
-    fn revive(&mut self) {
-        if self.health == 0 {
-            self.health = self.get_calculated_max_health();
-        }
-    }
-
-    fn get_name(&self) -> &str {
-        &self.name
-    }
-
-    fn get_health(&self) -> u32 {
-        self.health
-    }
-
-    fn set_health(&mut self, health: u32) {
-        self.health = health.min(self.max_health);
-    }
-
-    fn get_class_type(&self) -> &ClassType {
-        &self.class
-    }
-
-    fn get_character_type(&self) -> &CharacterType {
-        &self.character_type
-    }
-
-    fn get_base_health(&self) -> u32 {
-        200
-    }
-
-    fn get_damage_type(&self) -> DamageType {
-        DamageType::Physical
-    }
-
-    fn get_attack_type(&self) -> AttackType {
-        AttackType::Melee
-    }
-
-    fn get_base_damage(&self) -> u32 {
-        50
-    }
-
-    fn is_invincible(&self) -> bool {
-        self.health > self.max_health / 2
+pub fn dragon_boss() -> ActorTemplate {
+    ActorTemplate {
+        character_type: CharacterType::Enemy { threat_level: 15 },
+        base_health: 200,
+        base_damage: 50,
+        invincible_above: Some(0.5),
+        // Dragon scales shrug off blades but not spells
+        armor: Some(150),
+        magic_resistance: Some(0),
+        // Lumbering enough that nimble heroes get extra swings in
+        speed: Some(5),
+        aggro: 100,
+        affinities: vec![
+            (DamageType::Fire, Affinity::Immune),
+            (DamageType::Poison, Affinity::Immune),
+            (DamageType::Frost, Affinity::Weak),
+            (DamageType::Pierce, Affinity::Weak),
+            (DamageType::Slash, Affinity::Resistant),
+        ],
+        ..ActorTemplate::new("dragon_boss", "Dragon Boss")
     }
 }
 
-impl Enemy for DragonBoss {
-    fn get_aggro(&self) -> u32 {
-        100
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_types_spawn_their_presets() {
+        let goblin = GoblinWarrior::new("Azog");
+        assert_eq!(goblin.get_name(), "Azog");
+        assert_eq!(goblin.template.id, "goblin_warrior");
+        assert_eq!(GoblinMage::new("Gorbag").template.id, "goblin_mage");
 
-    fn get_threat_level(&self) -> u32 {
-        if let CharacterType::Enemy { threat_level } = &self.character_type {
-            *threat_level
-        } else {
-            10
-        }
+        let dragon = DragonBoss::new("Smaug");
+        assert_eq!(dragon.get_threat_level(), 15);
+        assert!(dragon.is_invincible());
     }
 }
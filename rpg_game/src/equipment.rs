@@ -0,0 +1,210 @@
+use std::fmt;
+
+use crate::classes::{AttackType, ClassType, DamageType};
+use crate::elements::{Affinities, Affinity};
+use crate::items::{Inventory, InventoryError, Item};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EquipSlot {
+    Weapon,
+    Armor,
+    Accessory,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClassRestriction {
+    Any,
+    Warrior,
+    Mage,
+}
+
+impl ClassRestriction {
+    pub fn allows(&self, class: &ClassType) -> bool {
+        matches!(
+            (self, class),
+            (ClassRestriction::Any, _)
+                | (ClassRestriction::Warrior, ClassType::Warrior(_))
+                | (ClassRestriction::Mage, ClassType::Mage(_))
+        )
+    }
+}
+
+// Stat changes from one piece of equipment. Damage and attack type replace the
+// wearer's own; everything else adds up across slots.
+#[derive(Debug, Clone)]
+pub struct Gear {
+    pub slot: EquipSlot,
+    pub class: ClassRestriction,
+    pub bonus_damage: u32,
+    pub bonus_health: u32,
+    pub armor: u32,
+    pub magic_resistance: u32,
+    pub damage_type: Option<DamageType>,
+    pub attack_type: Option<AttackType>,
+    pub affinities: Vec<(DamageType, Affinity)>,
+}
+
+impl Gear {
+    pub fn new(slot: EquipSlot, class: ClassRestriction) -> Self {
+        Gear {
+            slot,
+            class,
+            bonus_damage: 0,
+            bonus_health: 0,
+            armor: 0,
+            magic_resistance: 0,
+            damage_type: None,
+            attack_type: None,
+            affinities: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EquipError {
+    NotEquipment(String),
+    WrongClass {
+        item: String,
+        allowed: ClassRestriction,
+    },
+    EmptySlot(EquipSlot),
+    Inventory(InventoryError), // The item isn't carried, or the swapped out piece doesn't fit
+}
+
+impl fmt::Display for EquipError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EquipError::NotEquipment(name) => write!(f, "the {} can't be equipped", name),
+            EquipError::WrongClass { item, allowed } => {
+                write!(f, "only a {:?} can equip the {}", allowed, item)
+            }
+            EquipError::EmptySlot(slot) => write!(f, "nothing is equipped as {:?}", slot),
+            EquipError::Inventory(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for EquipError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EquipError::Inventory(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Equipment {
+    weapon: Option<Item>,
+    armor: Option<Item>,
+    accessory: Option<Item>,
+}
+
+impl Equipment {
+    pub fn get(&self, slot: EquipSlot) -> Option<&Item> {
+        match slot {
+            EquipSlot::Weapon => self.weapon.as_ref(),
+            EquipSlot::Armor => self.armor.as_ref(),
+            EquipSlot::Accessory => self.accessory.as_ref(),
+        }
+    }
+
+    fn slot_mut(&mut self, slot: EquipSlot) -> &mut Option<Item> {
+        match slot {
+            EquipSlot::Weapon => &mut self.weapon,
+            EquipSlot::Armor => &mut self.armor,
+            EquipSlot::Accessory => &mut self.accessory,
+        }
+    }
+
+    pub fn gear(&self) -> impl Iterator<Item = &Gear> {
+        [&self.weapon, &self.armor, &self.accessory]
+            .into_iter()
+            .flatten()
+            .filter_map(Item::gear)
+    }
+
+    // Moves the item out of the inventory, putting whatever it replaces back in its
+    // place. Nothing changes if any step fails.
+    pub fn equip(
+        &mut self,
+        id: &str,
+        class: &ClassType,
+        inventory: &mut Inventory,
+    ) -> Result<(), EquipError> {
+        let carried = inventory
+            .iter()
+            .map(|stack| &stack.item)
+            .find(|item| item.id == id)
+            .ok_or_else(|| EquipError::Inventory(InventoryError::NotCarried(id.to_string())))?;
+        let gear = carried
+            .gear()
+            .ok_or_else(|| EquipError::NotEquipment(carried.name.clone()))?;
+        if !gear.class.allows(class) {
+            return Err(EquipError::WrongClass {
+                item: carried.name.clone(),
+                allowed: gear.class,
+            });
+        }
+
+        let slot = gear.slot;
+        let item = inventory.remove(id, 1).map_err(EquipError::Inventory)?;
+        if let Some(previous) = self.slot_mut(slot).take()
+            && let Err(error) = inventory.add(previous.clone(), 1)
+        {
+            *self.slot_mut(slot) = Some(previous);
+            let _ = inventory.add(item, 1); // Its old space is still free
+            return Err(EquipError::Inventory(error));
+        }
+        *self.slot_mut(slot) = Some(item);
+        Ok(())
+    }
+
+    pub fn unequip(
+        &mut self,
+        slot: EquipSlot,
+        inventory: &mut Inventory,
+    ) -> Result<(), EquipError> {
+        let item = self
+            .slot_mut(slot)
+            .take()
+            .ok_or(EquipError::EmptySlot(slot))?;
+        if let Err(error) = inventory.add(item.clone(), 1) {
+            *self.slot_mut(slot) = Some(item);
+            return Err(EquipError::Inventory(error));
+        }
+        Ok(())
+    }
+
+    pub fn get_bonus_damage(&self) -> u32 {
+        self.gear().map(|gear| gear.bonus_damage).sum()
+    }
+
+    pub fn get_bonus_health(&self) -> u32 {
+        self.gear().map(|gear| gear.bonus_health).sum()
+    }
+
+    pub fn get_armor(&self) -> u32 {
+        self.gear().map(|gear| gear.armor).sum()
+    }
+
+    pub fn get_magic_resistance(&self) -> u32 {
+        self.gear().map(|gear| gear.magic_resistance).sum()
+    }
+
+    // The weapon wins over anything else that changes it
+    pub fn get_damage_type(&self) -> Option<DamageType> {
+        self.gear().find_map(|gear| gear.damage_type)
+    }
+
+    pub fn get_attack_type(&self) -> Option<AttackType> {
+        self.gear().find_map(|gear| gear.attack_type)
+    }
+
+    pub fn get_affinities(&self) -> Affinities {
+        self.gear().flat_map(|gear| gear.affinities.iter()).fold(
+            Affinities::default(),
+            |affinities, &(damage_type, affinity)| affinities.with(damage_type, affinity),
+        )
+    }
+}
@@ -0,0 +1,460 @@
+use crate::party::{PartyOutcome, Side};
+use crate::status::StatusKind;
+
+// Where a change in health came from
+#[derive(Debug, Clone, PartialEq)]
+pub enum EffectSource {
+    Action {
+        actor: String,
+        name: Option<String>, // Ability or spell, None for a plain attack
+    },
+    Status(StatusKind),
+    SuddenDeath,
+    OutOfCombat,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CombatEvent {
+    // Arena announcements
+    ArenaMatch {
+        player: String,
+        level: u32,
+        enemy: String,
+        threat: u32,
+    },
+    MonsterMatch {
+        first: String,
+        first_threat: u32,
+        second: String,
+        second_threat: u32,
+    },
+    PartyMatch {
+        players: usize,
+        enemies: usize,
+    },
+    TournamentStarted {
+        participants: usize,
+    },
+    TournamentRound {
+        round: u32,
+    },
+    Advanced {
+        name: String,
+    },
+    Bye {
+        name: String,
+    },
+
+    // Battle flow
+    RoundStarted {
+        round: u32,
+    },
+    TurnSkipped {
+        round: u32,
+        name: String,
+        status: StatusKind,
+    },
+    AbilityUsed {
+        round: u32,
+        user: String,
+        ability: String,
+    },
+    ItemUsed {
+        round: u32,
+        user: String,
+        item: String,
+    },
+    AttackPerformed {
+        round: u32,
+        attacker: String,
+        defender: String,
+        attack: Option<String>,
+        damage: u32,
+        critical: bool,
+        health_after: u32,
+    },
+    AttackMissed {
+        round: u32,
+        attacker: String,
+        defender: String,
+    },
+    DamageTaken {
+        round: u32,
+        target: String,
+        amount: u32,
+        source: EffectSource,
+        health_after: u32,
+    },
+    Healed {
+        round: Option<u32>, // None outside of battle
+        target: String,
+        amount: u32,
+        source: EffectSource,
+        health_after: u32,
+    },
+    ManaRestored {
+        round: Option<u32>, // None outside of battle
+        target: String,
+        amount: u32,
+        mana_after: u32,
+    },
+    StatusApplied {
+        round: u32,
+        target: String,
+        kind: StatusKind,
+    },
+    StatusExpired {
+        round: u32,
+        target: String,
+        kind: StatusKind,
+    },
+    Taunted {
+        round: u32,
+        user: String,
+        target: String,
+    },
+    CharacterDefeated {
+        round: u32,
+        name: String,
+    },
+    Fled {
+        round: u32,
+        name: String,
+    },
+
+    // Results and progression
+    BattleWon {
+        winner: String,
+        loser: String,
+        rounds: u32,
+    },
+    BattleDrawn {
+        rounds: u32,
+    },
+    PartyBattleEnded {
+        outcome: PartyOutcome,
+        rounds: u32,
+    },
+    ExperienceGained {
+        name: String,
+        amount: u32,
+    },
+    LevelUp {
+        name: String,
+        level: u32,
+    },
+    QuestProgress {
+        quest: String,
+        objective: String,
+        progress: u32,
+        required: u32,
+        completed: bool, // The whole quest, not just this objective
+    },
+    GameSaved {
+        name: String,
+        slot: u32,
+    },
+    SaveFailed {
+        slot: u32,
+        error: String,
+    },
+}
+
+pub trait CombatObserver {
+    fn on_event(&mut self, event: &CombatEvent);
+}
+
+// Any closure can subscribe, e.g. to collect events for a UI
+impl<F: FnMut(&CombatEvent)> CombatObserver for F {
+    fn on_event(&mut self, event: &CombatEvent) {
+        self(event)
+    }
+}
+
+// Prints the familiar console narration
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsoleLogger;
+
+impl CombatObserver for ConsoleLogger {
+    fn on_event(&mut self, event: &CombatEvent) {
+        match event {
+            CombatEvent::ArenaMatch {
+                player,
+                level,
+                enemy,
+                threat,
+            } => {
+                println!("=== ARENA COMBAT ===");
+                println!(
+                    "{} (Player, Level {}) vs {} (Enemy, Threat {})",
+                    player, level, enemy, threat
+                );
+            }
+            CombatEvent::MonsterMatch {
+                first,
+                first_threat,
+                second,
+                second_threat,
+            } => {
+                println!("=== MONSTER BATTLE ===");
+                println!(
+                    "{} (Threat {}) vs {} (Threat {})",
+                    first, first_threat, second, second_threat
+                );
+            }
+            CombatEvent::PartyMatch { players, enemies } => {
+                println!("=== PARTY BATTLE ===");
+                println!("{} heroes vs {} enemies", players, enemies);
+            }
+            CombatEvent::TournamentStarted { participants } => {
+                println!("=== TOURNAMENT START ===");
+                println!("Participants: {}", participants);
+            }
+            CombatEvent::TournamentRound { round } => println!("\n--- Round {} ---", round),
+            CombatEvent::Advanced { name } => println!("{} advances to the next round!", name),
+            CombatEvent::Bye { name } => println!("{} gets a bye to the next round!", name),
+            CombatEvent::RoundStarted { .. } => {} // Every line already carries its round
+            CombatEvent::TurnSkipped {
+                round,
+                name,
+                status,
+            } => println!("Round {}: {} loses a turn to {:?}!", round, name, status),
+            CombatEvent::AbilityUsed {
+                round,
+                user,
+                ability,
+            } => println!("Round {}: {} uses {}!", round, user, ability),
+            CombatEvent::ItemUsed { round, user, item } => {
+                println!("Round {}: {} uses a {}!", round, user, item)
+            }
+            CombatEvent::AttackPerformed {
+                round,
+                attacker,
+                defender,
+                attack,
+                damage,
+                critical,
+                health_after,
+            } => {
+                let action = match attack {
+                    Some(name) => format!("uses {} on", name),
+                    None => "attacks".to_string(),
+                };
+                println!(
+                    "Round {}: {} {} {} for {} damage!{} ({} HP remaining)",
+                    round,
+                    attacker,
+                    action,
+                    defender,
+                    damage,
+                    if *critical { " Critical hit!" } else { "" },
+                    health_after
+                );
+            }
+            CombatEvent::AttackMissed {
+                round,
+                attacker,
+                defender,
+            } => println!(
+                "Round {}: {} attacks {} but misses!",
+                round, attacker, defender
+            ),
+            // Attack damage is already narrated by AttackPerformed
+            CombatEvent::DamageTaken {
+                round,
+                target,
+                amount,
+                source: EffectSource::Status(kind),
+                health_after,
+            } => println!(
+                "Round {}: {} takes {} {:?} damage! ({} HP remaining)",
+                round, target, amount, kind, health_after
+            ),
+            CombatEvent::DamageTaken {
+                round,
+                target,
+                amount,
+                source: EffectSource::SuddenDeath,
+                health_after,
+            } => println!(
+                "Round {}: Sudden death! {} loses {} HP! ({} HP remaining)",
+                round, target, amount, health_after
+            ),
+            CombatEvent::DamageTaken { .. } => {}
+            CombatEvent::Healed {
+                round,
+                target,
+                amount,
+                source,
+                health_after,
+            } => match (round, source) {
+                (Some(round), EffectSource::Status(_)) => println!(
+                    "Round {}: {} regenerates {} HP! ({} HP)",
+                    round, target, amount, health_after
+                ),
+                (Some(round), EffectSource::Action { actor, name }) => println!(
+                    "Round {}: {} is healed for {} HP by {}! ({} HP)",
+                    round,
+                    target,
+                    amount,
+                    name.as_deref().unwrap_or(actor),
+                    health_after
+                ),
+                _ => println!(
+                    "{} healed for {} HP! Current HP: {}",
+                    target, amount, health_after
+                ),
+            },
+            CombatEvent::ManaRestored {
+                round,
+                target,
+                amount,
+                mana_after,
+            } => match round {
+                Some(round) => println!(
+                    "Round {}: {} restores {} mana! ({} mana)",
+                    round, target, amount, mana_after
+                ),
+                None => println!(
+                    "{} restores {} mana! Current mana: {}",
+                    target, amount, mana_after
+                ),
+            },
+            CombatEvent::StatusApplied {
+                round,
+                target,
+                kind,
+            } => println!("Round {}: {} is affected by {:?}!", round, target, kind),
+            CombatEvent::StatusExpired {
+                round,
+                target,
+                kind,
+            } => println!("Round {}: {:?} wears off {}", round, kind, target),
+            CombatEvent::Taunted {
+                round,
+                user,
+                target,
+            } => println!("Round {}: {} taunts {}!", round, user, target),
+            CombatEvent::CharacterDefeated { round, name } => {
+                println!("Round {}: {} has been defeated!", round, name)
+            }
+            CombatEvent::Fled { round, name } => {
+                println!("Round {}: {} flees the battle!", round, name)
+            }
+            CombatEvent::BattleWon {
+                winner,
+                loser,
+                rounds,
+            } => println!("{} defeats {} after {} rounds!", winner, loser, rounds),
+            CombatEvent::BattleDrawn { rounds } => {
+                println!("The battle ends in a draw after {} rounds!", rounds)
+            }
+            CombatEvent::PartyBattleEnded { outcome, rounds } => match outcome {
+                PartyOutcome::Won(Side::Players) => {
+                    println!("The party wins after {} rounds!", rounds)
+                }
+                PartyOutcome::Won(Side::Enemies) => {
+                    println!("The party has been defeated after {} rounds!", rounds)
+                }
+                PartyOutcome::Draw => println!("The party battle ends in a draw!"),
+            },
+            CombatEvent::ExperienceGained { name, amount } => {
+                println!("{} gains {} experience!", name, amount)
+            }
+            CombatEvent::LevelUp { name, level } => {
+                println!("{} reached level {}!", name, level)
+            }
+            CombatEvent::QuestProgress {
+                quest,
+                objective,
+                progress,
+                required,
+                completed,
+            } => {
+                println!("[{}] {}: {}/{}", quest, objective, progress, required);
+                if *completed {
+                    println!("[{}] Quest complete!", quest);
+                }
+            }
+            CombatEvent::GameSaved { name, slot } => {
+                println!("Autosaved {} to slot {}", name, slot)
+            }
+            CombatEvent::SaveFailed { slot, error } => {
+                println!("Autosave to slot {} failed: {}", slot, error)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::{Character, CombatSystem};
+    use crate::enemies::GoblinWarrior;
+    use crate::players::PlayerWarrior;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn recorder() -> (Rc<RefCell<Vec<CombatEvent>>>, impl FnMut(&CombatEvent)) {
+        let recorded = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&recorded);
+        (recorded, move |event: &CombatEvent| {
+            sink.borrow_mut().push(event.clone())
+        })
+    }
+
+    #[test]
+    fn every_observer_sees_the_whole_battle_in_order() {
+        let (first, first_observer) = recorder();
+        let (second, second_observer) = recorder();
+        let mut combat = CombatSystem::new()
+            .without_rng()
+            .with_observer(first_observer)
+            .with_observer(second_observer);
+        let mut hero = PlayerWarrior::new("Boromir");
+        let mut goblin = GoblinWarrior::new("Grishnakh");
+        combat.battle(&mut hero, &mut goblin);
+
+        let events = first.borrow();
+        assert_eq!(*events, *second.borrow());
+        assert_eq!(
+            events.first(),
+            Some(&CombatEvent::RoundStarted { round: 1 })
+        );
+        let Some(CombatEvent::BattleWon { loser, rounds, .. }) = events.last() else {
+            panic!(
+                "the battle should end with a winner, got {:?}",
+                events.last()
+            );
+        };
+        assert!(events.contains(&CombatEvent::CharacterDefeated {
+            round: *rounds,
+            name: loser.clone(),
+        }));
+    }
+
+    #[test]
+    fn attacks_name_both_fighters() {
+        let (recorded, observer) = recorder();
+        let mut combat = CombatSystem::new().without_rng().with_observer(observer);
+        let mut hero = PlayerWarrior::new("Boromir");
+        let mut goblin = GoblinWarrior::new("Grishnakh");
+        combat.battle(&mut hero, &mut goblin);
+
+        let attacks: Vec<_> = recorded
+            .borrow()
+            .iter()
+            .filter_map(|event| match event {
+                CombatEvent::AttackPerformed {
+                    attacker, defender, ..
+                } => Some((attacker.clone(), defender.clone())),
+                _ => None,
+            })
+            .collect();
+        assert!(!attacks.is_empty());
+        for (attacker, defender) in attacks {
+            assert_ne!(attacker, defender);
+            assert!(["Boromir", "Grishnakh"].contains(&attacker.as_str()));
+        }
+    }
+}
@@ -0,0 +1,57 @@
+// Much faster characters act more than once per round, up to this many times
+pub const MAX_ACTIONS_PER_ROUND: u32 = 3;
+
+pub fn actions_per_round(speed: u32, slowest_speed: u32) -> u32 {
+    (speed.max(1) / slowest_speed.max(1)).clamp(1, MAX_ACTIONS_PER_ROUND)
+}
+
+// Returns combatant indices in acting order for one round. Faster combatants go first,
+// equal speeds are ordered by the lower tie key, and extra turns are interleaved so a
+// double-speed fighter acts first and last rather than twice in a row.
+pub fn initiative_order(speeds: &[u32], tie_keys: &[u32]) -> Vec<usize> {
+    let mut ranked: Vec<usize> = (0..speeds.len()).collect();
+    ranked.sort_by(|&a, &b| {
+        speeds[b]
+            .cmp(&speeds[a])
+            .then_with(|| tie_keys[a].cmp(&tie_keys[b]))
+    });
+
+    let slowest = speeds.iter().copied().min().unwrap_or(0);
+    let mut order = Vec::new();
+    for pass in 0..MAX_ACTIONS_PER_ROUND {
+        for &index in &ranked {
+            if actions_per_round(speeds[index], slowest) > pass {
+                order.push(index);
+            }
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::{Character, CombatSystem};
+    use crate::players::PlayerWarrior;
+    use crate::status::{StatusEffect, StatusKind};
+
+    #[test]
+    fn a_stopped_fighter_does_not_hand_out_extra_turns() {
+        assert_eq!(actions_per_round(0, 0), 1);
+        assert_eq!(actions_per_round(5, 0), 3);
+        assert_eq!(initiative_order(&[0, 0], &[0, 1]), vec![0, 1]);
+    }
+
+    #[test]
+    fn a_fully_slowed_fighter_acts_once_while_the_other_acts_three_times() {
+        let mut slowed = PlayerWarrior::new("Merry");
+        slowed.apply_status(StatusEffect::new(StatusKind::Slow, 100, 2));
+        let swift = PlayerWarrior::new("Pippin");
+        assert_eq!(slowed.get_effective_speed(), 1);
+
+        let order = CombatSystem::new()
+            .without_rng()
+            .turn_order(1, &[&slowed, &swift]);
+        assert_eq!(order, vec![1, 0, 1, 1]);
+    }
+}
@@ -0,0 +1,520 @@
+use std::fmt;
+
+use crate::character::{Character, heal};
+use crate::classes::{AttackType, DamageType};
+use crate::elements::Affinity;
+use crate::equipment::{ClassRestriction, EquipSlot, Gear};
+use crate::status::{StatusEffect, StatusKind};
+
+pub const DEFAULT_SLOTS: usize = 10;
+pub const DEFAULT_MAX_WEIGHT: u32 = 30;
+pub const STOCK_SLOTS: usize = 40; // Merchants carry a whole shop
+pub const STOCK_MAX_WEIGHT: u32 = 500;
+
+#[derive(Debug, Clone)]
+pub enum ItemEffect {
+    Heal(u32),
+    RestoreMana(u32),
+    ApplyStatus(StatusEffect), // Always on the user
+}
+
+#[derive(Debug, Clone)]
+pub enum ItemKind {
+    Consumable(Vec<ItemEffect>), // Stacks, used up on use
+    Unique,                      // Keys, trophies and keepsakes; one of each at most
+    Equipment(Gear),             // Worn in a slot, also one of each
+}
+
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub id: String,
+    pub name: String,
+    pub weight: u32,
+    pub value: u32, // Base price in gold
+    pub kind: ItemKind,
+}
+
+impl Item {
+    pub fn is_stackable(&self) -> bool {
+        matches!(self.kind, ItemKind::Consumable(_))
+    }
+
+    pub fn effects(&self) -> &[ItemEffect] {
+        match &self.kind {
+            ItemKind::Consumable(effects) => effects,
+            ItemKind::Unique | ItemKind::Equipment(_) => &[],
+        }
+    }
+
+    pub fn gear(&self) -> Option<&Gear> {
+        match &self.kind {
+            ItemKind::Equipment(gear) => Some(gear),
+            _ => None,
+        }
+    }
+
+    fn heals(&self) -> bool {
+        self.effects()
+            .iter()
+            .any(|effect| matches!(effect, ItemEffect::Heal(_)))
+    }
+
+    fn restores_mana(&self) -> bool {
+        self.effects()
+            .iter()
+            .any(|effect| matches!(effect, ItemEffect::RestoreMana(_)))
+    }
+}
+
+fn consumable(id: &str, name: &str, value: u32, effects: Vec<ItemEffect>) -> Item {
+    Item {
+        id: id.to_string(),
+        name: name.to_string(),
+        weight: 1,
+        value,
+        kind: ItemKind::Consumable(effects),
+    }
+}
+
+fn unique(id: &str, name: &str, weight: u32, value: u32) -> Item {
+    Item {
+        id: id.to_string(),
+        name: name.to_string(),
+        weight,
+        value,
+        kind: ItemKind::Unique,
+    }
+}
+
+fn equipment(id: &str, name: &str, weight: u32, value: u32, gear: Gear) -> Item {
+    Item {
+        id: id.to_string(),
+        name: name.to_string(),
+        weight,
+        value,
+        kind: ItemKind::Equipment(gear),
+    }
+}
+
+pub fn item_catalogue() -> Vec<Item> {
+    vec![
+        consumable(
+            "health_potion",
+            "Health Potion",
+            25,
+            vec![ItemEffect::Heal(40)],
+        ),
+        consumable(
+            "greater_health_potion",
+            "Greater Health Potion",
+            70,
+            vec![ItemEffect::Heal(100)],
+        ),
+        consumable(
+            "mana_potion",
+            "Mana Potion",
+            30,
+            vec![ItemEffect::RestoreMana(40)],
+        ),
+        consumable(
+            "regeneration_draught",
+            "Regeneration Draught",
+            45,
+            vec![
+                ItemEffect::Heal(10),
+                ItemEffect::ApplyStatus(StatusEffect::new(StatusKind::Regeneration, 8, 3)),
+            ],
+        ),
+        unique("ancient_key", "Ancient Key", 0, 0),
+        unique("dragon_scale", "Dragon Scale", 5, 500),
+        equipment(
+            "iron_sword",
+            "Iron Sword",
+            4,
+            60,
+            Gear {
+                bonus_damage: 8,
+                ..Gear::new(EquipSlot::Weapon, ClassRestriction::Warrior)
+            },
+        ),
+        equipment(
+            "flamebrand",
+            "Flamebrand",
+            4,
+            300,
+            Gear {
+                bonus_damage: 5,
+                damage_type: Some(DamageType::Fire),
+                ..Gear::new(EquipSlot::Weapon, ClassRestriction::Warrior)
+            },
+        ),
+        equipment(
+            "longbow",
+            "Longbow",
+            3,
+            90,
+            Gear {
+                bonus_damage: 4,
+                damage_type: Some(DamageType::Pierce),
+                attack_type: Some(AttackType::Ranged),
+                ..Gear::new(EquipSlot::Weapon, ClassRestriction::Warrior)
+            },
+        ),
+        equipment(
+            "oak_staff",
+            "Oak Staff",
+            3,
+            80,
+            Gear {
+                bonus_damage: 6,
+                ..Gear::new(EquipSlot::Weapon, ClassRestriction::Mage)
+            },
+        ),
+        equipment(
+            "chainmail",
+            "Chainmail",
+            10,
+            120,
+            Gear {
+                bonus_health: 30,
+                armor: 15,
+                ..Gear::new(EquipSlot::Armor, ClassRestriction::Warrior)
+            },
+        ),
+        equipment(
+            "silk_robe",
+            "Silk Robe",
+            2,
+            100,
+            Gear {
+                bonus_health: 10,
+                magic_resistance: 15,
+                ..Gear::new(EquipSlot::Armor, ClassRestriction::Mage)
+            },
+        ),
+        equipment(
+            "frost_amulet",
+            "Frost Amulet",
+            1,
+            150,
+            Gear {
+                magic_resistance: 5,
+                affinities: vec![(DamageType::Frost, Affinity::Resistant)],
+                ..Gear::new(EquipSlot::Accessory, ClassRestriction::Any)
+            },
+        ),
+        equipment(
+            "ring_of_vigor",
+            "Ring of Vigor",
+            0,
+            200,
+            Gear {
+                bonus_health: 20,
+                ..Gear::new(EquipSlot::Accessory, ClassRestriction::Any)
+            },
+        ),
+    ]
+}
+
+pub fn item_by_id(id: &str) -> Option<Item> {
+    item_catalogue().into_iter().find(|item| item.id == id)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InventoryError {
+    NoInventory,
+    UnknownItem(String),
+    NoFreeSlot,
+    TooHeavy { weight: u32, max_weight: u32 },
+    AlreadyOwned(String),
+    NotCarried(String),
+    NotEnough { id: String, carried: u32 },
+    NotUsable(String),
+    Defeated(String), // Potions don't raise the dead
+}
+
+impl fmt::Display for InventoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InventoryError::NoInventory => write!(f, "can't carry items"),
+            InventoryError::UnknownItem(id) => write!(f, "unknown item `{}`", id),
+            InventoryError::NoFreeSlot => write!(f, "no free inventory slot"),
+            InventoryError::TooHeavy { weight, max_weight } => write!(
+                f,
+                "that would weigh {}, more than the {} that can be carried",
+                weight, max_weight
+            ),
+            InventoryError::AlreadyOwned(name) => write!(f, "can only carry one {}", name),
+            InventoryError::NotCarried(id) => write!(f, "not carrying any `{}`", id),
+            InventoryError::NotEnough { id, carried } => {
+                write!(f, "only carrying {} of `{}`", carried, id)
+            }
+            InventoryError::NotUsable(name) => write!(f, "the {} can't be used", name),
+            InventoryError::Defeated(name) => write!(f, "{} is down and can't use items", name),
+        }
+    }
+}
+
+impl std::error::Error for InventoryError {}
+
+#[derive(Debug, Clone)]
+pub struct ItemStack {
+    pub item: Item,
+    pub quantity: u32,
+}
+
+// Limited both by distinct stacks (slots) and by total weight
+#[derive(Debug, Clone)]
+pub struct Inventory {
+    stacks: Vec<ItemStack>,
+    max_slots: usize,
+    max_weight: u32,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Inventory::new(DEFAULT_SLOTS, DEFAULT_MAX_WEIGHT)
+    }
+}
+
+impl Inventory {
+    pub fn new(max_slots: usize, max_weight: u32) -> Self {
+        Inventory {
+            stacks: Vec::new(),
+            max_slots,
+            max_weight,
+        }
+    }
+
+    // All or nothing: either every copy fits or the inventory is left untouched
+    pub fn add(&mut self, item: Item, quantity: u32) -> Result<(), InventoryError> {
+        let existing = self
+            .stacks
+            .iter()
+            .position(|stack| stack.item.id == item.id);
+        if !item.is_stackable() && (existing.is_some() || quantity > 1) {
+            return Err(InventoryError::AlreadyOwned(item.name));
+        }
+        if existing.is_none() && self.stacks.len() >= self.max_slots {
+            return Err(InventoryError::NoFreeSlot);
+        }
+        // Anything too heavy to even count is certainly too heavy to carry
+        let weight = item
+            .weight
+            .checked_mul(quantity)
+            .and_then(|added| added.checked_add(self.weight()))
+            .unwrap_or(u32::MAX);
+        if weight > self.max_weight {
+            return Err(InventoryError::TooHeavy {
+                weight,
+                max_weight: self.max_weight,
+            });
+        }
+
+        match existing {
+            Some(index) => self.stacks[index].quantity += quantity,
+            None => self.stacks.push(ItemStack { item, quantity }),
+        }
+        Ok(())
+    }
+
+    pub fn add_by_id(&mut self, id: &str, quantity: u32) -> Result<(), InventoryError> {
+        let item = item_by_id(id).ok_or_else(|| InventoryError::UnknownItem(id.to_string()))?;
+        self.add(item, quantity)
+    }
+
+    // Hands back one copy of the removed item
+    pub fn remove(&mut self, id: &str, quantity: u32) -> Result<Item, InventoryError> {
+        let index = self
+            .stacks
+            .iter()
+            .position(|stack| stack.item.id == id)
+            .ok_or_else(|| InventoryError::NotCarried(id.to_string()))?;
+        let stack = &mut self.stacks[index];
+        if stack.quantity < quantity {
+            return Err(InventoryError::NotEnough {
+                id: id.to_string(),
+                carried: stack.quantity,
+            });
+        }
+
+        stack.quantity -= quantity;
+        if stack.quantity == 0 {
+            Ok(self.stacks.remove(index).item)
+        } else {
+            Ok(stack.item.clone())
+        }
+    }
+
+    pub fn count(&self, id: &str) -> u32 {
+        self.stacks
+            .iter()
+            .find(|stack| stack.item.id == id)
+            .map_or(0, |stack| stack.quantity)
+    }
+
+    pub fn weight(&self) -> u32 {
+        self.stacks
+            .iter()
+            .map(|stack| stack.item.weight * stack.quantity)
+            .sum()
+    }
+
+    pub fn free_slots(&self) -> usize {
+        self.max_slots.saturating_sub(self.stacks.len())
+    }
+
+    pub fn get_max_weight(&self) -> u32 {
+        self.max_weight
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ItemStack> {
+        self.stacks.iter()
+    }
+
+    // Potions are saved for emergencies: below a third of max health, or when the
+    // next attack can't be paid for
+    pub fn choose(&self, user: &dyn Character) -> Option<&Item> {
+        if !user.is_alive() {
+            return None;
+        }
+        let wants_healing = user.get_health() < user.get_calculated_max_health() / 3;
+        let wants_mana = user.get_attack().mana_cost > user.get_mana();
+        self.stacks
+            .iter()
+            .map(|stack| &stack.item)
+            .find(|item| (wants_healing && item.heals()) || (wants_mana && item.restores_mana()))
+    }
+}
+
+// What using an item actually did
+#[derive(Debug, Clone, Default)]
+pub struct ItemUse {
+    pub item: String,
+    pub healed: u32,
+    pub mana_restored: u32,
+    pub statuses: Vec<StatusKind>,
+}
+
+pub fn apply_item(item: &Item, user: &mut dyn Character) -> ItemUse {
+    let mut used = ItemUse {
+        item: item.name.clone(),
+        ..ItemUse::default()
+    };
+    for effect in item.effects() {
+        match effect {
+            ItemEffect::Heal(amount) => {
+                let health_before = user.get_health();
+                heal(user, *amount);
+                used.healed += user.get_health().saturating_sub(health_before);
+            }
+            ItemEffect::RestoreMana(amount) => {
+                let mana_before = user.get_mana();
+                user.restore_mana(*amount);
+                used.mana_restored += user.get_mana() - mana_before;
+            }
+            ItemEffect::ApplyStatus(status) => {
+                user.apply_status(status.clone());
+                used.statuses.push(status.kind);
+            }
+        }
+    }
+    used
+}
+
+// Takes one of the item from the character's own inventory and uses it
+pub fn use_item(user: &mut dyn Character, id: &str) -> Result<ItemUse, InventoryError> {
+    if !user.is_alive() {
+        return Err(InventoryError::Defeated(user.get_name().to_string()));
+    }
+    let inventory = user
+        .get_inventory_mut()
+        .ok_or(InventoryError::NoInventory)?;
+    let carried = inventory.iter().find(|stack| stack.item.id == id);
+    match carried.map(|stack| &stack.item) {
+        None => Err(InventoryError::NotCarried(id.to_string())),
+        Some(item) if !item.is_stackable() => Err(InventoryError::NotUsable(item.name.clone())),
+        Some(_) => {
+            let item = inventory.remove(id, 1)?;
+            Ok(apply_item(&item, user))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn potion() -> Item {
+        item_by_id("health_potion").unwrap()
+    }
+
+    #[test]
+    fn potions_stack_into_one_slot() {
+        let mut inventory = Inventory::default();
+        inventory.add(potion(), 2).unwrap();
+        inventory.add(potion(), 3).unwrap();
+        assert_eq!(inventory.count("health_potion"), 5);
+        assert_eq!(inventory.free_slots(), DEFAULT_SLOTS - 1);
+    }
+
+    #[test]
+    fn only_one_of_a_unique_item_fits() {
+        let mut inventory = Inventory::default();
+        let gear = item_by_id("chainmail").unwrap();
+        assert!(matches!(
+            inventory.add(gear.clone(), 2),
+            Err(InventoryError::AlreadyOwned(_))
+        ));
+        inventory.add(gear.clone(), 1).unwrap();
+        assert!(matches!(
+            inventory.add(gear, 1),
+            Err(InventoryError::AlreadyOwned(_))
+        ));
+    }
+
+    #[test]
+    fn rejected_adds_leave_the_inventory_untouched() {
+        let mut inventory = Inventory::new(1, 5);
+        inventory.add(potion(), 4).unwrap();
+        assert!(matches!(
+            inventory.add(potion(), 2),
+            Err(InventoryError::TooHeavy { weight: 6, .. })
+        ));
+        assert_eq!(
+            inventory.add_by_id("mana_potion", 1),
+            Err(InventoryError::NoFreeSlot)
+        );
+        assert_eq!(inventory.count("health_potion"), 4);
+        assert_eq!(inventory.weight(), 4);
+    }
+
+    #[test]
+    fn huge_quantities_are_too_heavy_instead_of_overflowing() {
+        let mut inventory = Inventory::new(DEFAULT_SLOTS, u32::MAX - 1);
+        inventory.add(potion(), 1).unwrap();
+        assert!(matches!(
+            inventory.add(potion(), u32::MAX),
+            Err(InventoryError::TooHeavy {
+                weight: u32::MAX,
+                ..
+            })
+        ));
+        assert_eq!(inventory.count("health_potion"), 1);
+    }
+
+    #[test]
+    fn removing_more_than_carried_fails() {
+        let mut inventory = Inventory::default();
+        inventory.add(potion(), 2).unwrap();
+        assert_eq!(
+            inventory.remove("health_potion", 3).unwrap_err(),
+            InventoryError::NotEnough {
+                id: "health_potion".to_string(),
+                carried: 2
+            }
+        );
+        inventory.remove("health_potion", 2).unwrap();
+        assert_eq!(inventory.free_slots(), DEFAULT_SLOTS);
+    }
+}
@@ -1,19 +1,61 @@
+mod abilities;
+mod actor;
+mod builder;
 mod character;
 mod classes;
 mod combat;
+mod config;
+mod damage;
+mod economy;
+mod elements;
 mod enemies;
+mod equipment;
+mod events;
+mod initiative;
+mod items;
 mod npcs;
+mod party;
 mod players;
+mod quests;
+mod registry;
+mod report;
+mod roster;
+mod save;
+mod status;
+mod templates;
+mod threat;
+mod trade;
 
+use actor::{ActorTemplate, Preset};
+use builder::CharacterBuilder;
 use character::{
-    Character, CombatSystem, attack, compare_characters, find_strongest, find_tankiest, heal,
-    heal_party, party_total_health,
+    BattleResult, Character, CharacterType, CombatSystem, NPCImportance, attack, attack_with,
+    compare_characters, find_strongest, find_tankiest, heal, heal_party, party_total_health,
 };
-use classes::{Class, ClassType};
+use classes::{Class, ClassType, DamageType};
 use combat::Arena;
+use config::{BattleConfig, FirstStrike};
+use damage::{DamagePipeline, Multiplier};
+use economy::{Order, simulate, simulate_week};
+use elements::{Affinity, EffectivenessTable};
 use enemies::{DragonBoss, Enemy, GoblinMage, GoblinWarrior};
+use equipment::EquipSlot;
+use events::{CombatEvent, CombatObserver, ConsoleLogger};
+use items::{Inventory, item_by_id, item_catalogue, use_item};
 use npcs::{LegendaryNPC, Merchant, NPC, QuestGiver, Villager};
+use party::{PartyRules, TargetSelection, VictoryCondition};
 use players::{Player, PlayerMage, PlayerWarrior};
+use quests::{QuestLog, QuestUpdate};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use registry::CharacterRegistry;
+use roster::Combatant;
+use save::{MIGRATIONS, Persist, SAVE_VERSION, SaveRecord, SaveSlots, SavedPlayer};
+use status::{StatusEffect, StatusKind};
+use std::cell::RefCell;
+use std::rc::Rc;
+use templates::TemplateLibrary;
+use trade::{DEFAULT_BUYBACK, DEFAULT_MARKUP, Shop, Trade, TradeError};
 
 fn main() {
     println!("=== RPG GAME SYSTEM ===\n");
@@ -28,6 +70,7 @@ fn main() {
     println!("=== CLASS SYSTEM EXPLORATION ===");
 
     // Demonstrate class type usage and internal class access
+    #[allow(clippy::single_match)]
     match warrior.get_class_type() {
         ClassType::Warrior(warrior_class) => {
             println!("Warrior Class Stats:");
@@ -39,6 +82,7 @@ fn main() {
         _ => {}
     }
 
+    #[allow(clippy::single_match)]
     match mage.get_class_type() {
         ClassType::Mage(mage_class) => {
             println!("\nMage Class Stats:");
@@ -79,12 +123,18 @@ fn main() {
 
     // Goblin warrior special abilities
     println!("Goblin Warrior Stats:");
-    println!("  Strength: {}", goblin1.get_strength());
+    if let Some(strength) = goblin1.get_class_type().get_strength() {
+        println!("  Strength: {}", strength);
+    }
     println!("  Aggro Level: {}", goblin1.get_aggro());
     println!("  Threat Level: {}", goblin1.get_threat_level());
 
-    let berserker_damage = goblin1.berserker_rage();
-    println!("  Berserker Rage Bonus: {}", berserker_damage);
+    for (ability, _) in goblin1.get_abilities().iter() {
+        println!(
+            "  Ability: {} (cooldown {}, {:?})",
+            ability.name, ability.cooldown, ability.effects
+        );
+    }
 
     println!("\nGoblin Mage Stats:");
     println!("  Aggro Level: {}", goblin2.get_aggro());
@@ -112,6 +162,9 @@ fn main() {
         println!("Can Give Quests: {}", npc.can_give_quests());
         println!("{}", npc.interact());
     }
+    for quest in QuestLog::offered_by(&legendary_npc) {
+        println!("{} offers \"{}\"", legendary_npc.get_name(), quest.name);
+    }
 
     println!("\n=== MERCHANT TRADING SYSTEM ===");
     let mut active_merchant = Merchant::new("Bree Trader");
@@ -137,6 +190,180 @@ fn main() {
         active_merchant.get_gold()
     );
 
+    let mut shop = Shop::new();
+    println!(
+        "{}'s stock (selling at {}x value, buying back at {}x):",
+        active_merchant.get_name(),
+        DEFAULT_MARKUP,
+        DEFAULT_BUYBACK
+    );
+    for stack in active_merchant.inventory.iter() {
+        println!(
+            "  {} x{}: sells for {} gold, buys for {} gold",
+            stack.item.name,
+            stack.quantity,
+            shop.sell_price(&stack.item),
+            shop.buy_price(&stack.item)
+        );
+    }
+
+    let report_trade = |verb: &str, trade: Result<Trade, TradeError>| match trade {
+        Ok(trade) => println!(
+            "  {} {} x{} at {} gold each ({} total)",
+            verb, trade.item, trade.quantity, trade.unit_price, trade.total
+        ),
+        Err(error) => println!("  Refused: {}", error),
+    };
+    let mut shopper = PlayerWarrior::new("Pippin");
+    shopper.gold = 120;
+    let _ = shopper.inventory.add_by_id("dragon_scale", 1);
+    let _ = shopper.inventory.add_by_id("ancient_key", 1);
+    println!(
+        "{} goes shopping with {} gold:",
+        shopper.get_name(),
+        shopper.gold
+    );
+    let purchases = [
+        ("health_potion", 2),
+        ("iron_sword", 1),
+        ("chainmail", 1),
+        ("mana_potion", 7),
+        ("iron_sword", 1),
+        ("health_potion", 0),
+    ];
+    for (id, quantity) in purchases {
+        report_trade(
+            "Bought",
+            shop.buy(&mut active_merchant, &mut shopper, id, quantity),
+        );
+    }
+
+    let mut pedlar = Merchant::new("Poor Pedlar");
+    pedlar.gold = 100;
+    report_trade(
+        "Sold",
+        shop.sell(&mut pedlar, &mut shopper, "dragon_scale", 1),
+    );
+    for (id, quantity) in [
+        ("dragon_scale", 1),
+        ("ancient_key", 1),
+        ("health_potion", 5),
+    ] {
+        report_trade(
+            "Sold",
+            shop.sell(&mut active_merchant, &mut shopper, id, quantity),
+        );
+    }
+    let mut gossip = Villager::new("Gossiping Villager");
+    report_trade(
+        "Bought",
+        shop.buy(&mut gossip, &mut shopper, "health_potion", 1),
+    );
+
+    let mut urchin = PlayerMage::new("Urchin");
+    urchin.gold = 100;
+    urchin.inventory = Inventory::new(1, 5);
+    for id in ["health_potion", "mana_potion"] {
+        report_trade("Bought", shop.buy(&mut active_merchant, &mut urchin, id, 1));
+    }
+
+    println!(
+        "Afterwards {} has {} gold and {} has {} gold, {} health potions left in stock",
+        shopper.get_name(),
+        shopper.gold,
+        active_merchant.get_name(),
+        active_merchant.get_gold(),
+        active_merchant.inventory.count("health_potion")
+    );
+    let greedy_shop = Shop::new().with_markup(1.5).with_buyback(0.25);
+    if let Some(sword) = item_by_id("iron_sword") {
+        println!(
+            "A greedier shop sells the {} for {} gold and buys it for {}",
+            sword.name,
+            greedy_shop.sell_price(&sword),
+            greedy_shop.buy_price(&sword)
+        );
+    }
+
+    println!("\n=== MARKET PRICES ===");
+
+    if let Some(potion) = item_by_id("health_potion") {
+        println!(
+            "Day {}: health potions sell for {} gold ({:.2}x after {}'s shopping)",
+            shop.get_day(),
+            shop.sell_price(&potion),
+            shop.get_price_factor("health_potion"),
+            shopper.get_name()
+        );
+        let mut rush = PlayerWarrior::new("Rush Buyer");
+        rush.gold = 1000;
+        for _ in 0..3 {
+            report_trade(
+                "Bought",
+                shop.buy(&mut active_merchant, &mut rush, "health_potion", 2),
+            );
+        }
+        shop.pass_days(&mut active_merchant, 2);
+        println!(
+            "Day {}: restocked to {} potions, price back down to {} gold",
+            shop.get_day(),
+            active_merchant.inventory.count("health_potion"),
+            shop.sell_price(&potion)
+        );
+
+        let loyal_shop = Shop::new().with_reputation("Pippin", 12);
+        let mut sage = LegendaryNPC::new("Tom Bombadil");
+        sage.inventory = active_merchant.inventory.clone();
+        let mut hawker = Merchant::new("Hawker");
+        hawker.template.character_type = CharacterType::NPC {
+            importance: NPCImportance::Minor,
+        };
+        println!(
+            "Pippin has {} reputation ({:.0}% off) at the loyal shop, {} after trading here",
+            loyal_shop.get_reputation("Pippin"),
+            loyal_shop.get_discount("Pippin") * 100.0,
+            shop.get_reputation("Pippin")
+        );
+        for (merchant, customer) in [
+            (&active_merchant, "Stranger"),
+            (&active_merchant, "Pippin"),
+            (&hawker, "Stranger"),
+            (&sage, "Stranger"),
+        ] {
+            println!(
+                "  {} quotes {} gold to {} ({:?})",
+                merchant.get_name(),
+                loyal_shop.quote(&potion, merchant, customer),
+                customer,
+                merchant.get_importance()
+            );
+        }
+    }
+
+    let mut week_merchant = Merchant::new("Market Stall");
+    let mut week_shop = Shop::new();
+    let orders = [
+        Order::buy("Adventurers", "health_potion", 4),
+        Order::buy("Apprentices", "mana_potion", 2),
+        Order::sell("Herbalist", "health_potion", 1),
+        Order::buy("Knight", "chainmail", 1),
+    ];
+    let week = simulate_week(&mut week_shop, &mut week_merchant, &orders);
+    println!("A week at {}:", week_merchant.get_name());
+    print!("{}", week);
+    if let Some(curve) = week.price_curve("health_potion") {
+        println!("Health potion price curve: {:?}", curve);
+    }
+    if let Some(refusal) = week.days.iter().flat_map(|day| &day.refusals).next() {
+        println!("First refusal: {}", refusal);
+    }
+    let mut quiet_merchant = Merchant::new("Quiet Stall");
+    let quiet = simulate(&mut Shop::new(), &mut quiet_merchant, &[], 2);
+    println!(
+        "With no customers prices stay at {:?}",
+        quiet.days.last().map(|day| &day.prices)
+    );
+
     println!("\n=== PLAYER PROGRESSION SYSTEM ===");
 
     println!("Initial Player Stats:");
@@ -174,7 +401,7 @@ fn main() {
     println!("\n=== COMBAT SYSTEM DEMONSTRATIONS ===");
 
     println!("Basic Attack System:");
-    let damage1 = attack(&warrior, &mut goblin1);
+    let damage1 = attack(&mut warrior, &mut goblin1);
     println!(
         "{} attacks {} for {} damage!",
         warrior.get_name(),
@@ -182,7 +409,7 @@ fn main() {
         damage1
     );
 
-    let damage2 = attack(&mage, &mut goblin2);
+    let damage2 = attack(&mut mage, &mut goblin2);
     println!(
         "{} attacks {} for {} damage!",
         mage.get_name(),
@@ -218,7 +445,9 @@ fn main() {
         test_player.get_experience()
     );
 
-    Arena::player_vs_enemy(&mut test_player, &mut test_enemy);
+    let mut arena = Arena::with_combat(CombatSystem::new().with_observer(ConsoleLogger));
+    let arena_report = arena.player_vs_enemy(&mut test_player, &mut test_enemy);
+    print!("{}", arena_report);
 
     println!("After combat:");
     println!(
@@ -241,6 +470,90 @@ fn main() {
         mage_damage_vs_warrior
     );
 
+    println!("\n=== DEFENSE AND RESISTANCE ===");
+    for (name, armor, resistance) in [
+        (
+            warrior.get_name(),
+            warrior.get_armor(),
+            warrior.get_magic_resistance(),
+        ),
+        (
+            mage.get_name(),
+            mage.get_armor(),
+            mage.get_magic_resistance(),
+        ),
+        (
+            dragon.get_name(),
+            dragon.get_armor(),
+            dragon.get_magic_resistance(),
+        ),
+        (
+            villager.get_name(),
+            villager.get_armor(),
+            villager.get_magic_resistance(),
+        ),
+    ] {
+        println!(
+            "  {}: {} armor, {} magic resistance",
+            name, armor, resistance
+        );
+    }
+    println!(
+        "  Warrior vs {}: {} damage, Mage vs {}: {} damage",
+        dragon.get_name(),
+        combat::calculate_damage_with_bonus(&warrior, &dragon),
+        dragon.get_name(),
+        combat::calculate_damage_with_bonus(&mage, &dragon)
+    );
+
+    println!("\n=== ELEMENTAL EFFECTIVENESS ===");
+    let elements = EffectivenessTable::default();
+    for (attack_type, defend_type) in [
+        (DamageType::Fire, DamageType::Frost),
+        (DamageType::Holy, DamageType::Shadow),
+        (DamageType::Slash, DamageType::Fire),
+        (DamageType::Shadow, DamageType::Physical),
+    ] {
+        println!(
+            "  {:?} vs {:?}: x{:.2}",
+            attack_type,
+            defend_type,
+            elements.multiplier(attack_type, defend_type)
+        );
+    }
+
+    let dragon_affinities = dragon.get_affinities();
+    println!("{} innate affinities:", dragon.get_name());
+    for damage_type in [
+        DamageType::Fire,
+        DamageType::Frost,
+        DamageType::Pierce,
+        DamageType::Slash,
+        DamageType::Holy,
+    ] {
+        match dragon_affinities.get(damage_type) {
+            Some(affinity) => println!(
+                "  {:?}: {:?} (x{:.1})",
+                damage_type,
+                affinity,
+                dragon_affinities.get_multiplier(damage_type)
+            ),
+            None => println!("  {:?}: Neutral", damage_type),
+        }
+    }
+
+    println!("\n=== DAMAGE PIPELINE BREAKDOWN ===");
+    let balance_pipeline = DamagePipeline::default().with_stage(Multiplier::new("Arena buff", 1.1));
+    let mut training_goblin = GoblinWarrior::new("Training Goblin");
+    if let Some(hit) = attack_with(&balance_pipeline, &mut warrior, &mut training_goblin) {
+        println!(
+            "{} hits {} with the arena buff:",
+            warrior.get_name(),
+            training_goblin.get_name()
+        );
+        println!("{}", hit);
+    }
+
     println!("\n=== PARTY OPERATIONS ===");
 
     let mut party = vec![
@@ -258,7 +571,7 @@ fn main() {
         "Party total health before healing: {}",
         party_total_health(&party)
     );
-    heal_party(&mut party, 25);
+    heal_party(&mut party, 25, &mut ConsoleLogger);
     println!(
         "Party total health after healing: {}",
         party_total_health(&party)
@@ -310,8 +623,12 @@ fn main() {
         GoblinWarrior::new("Grishnakh"),
     ];
 
-    if let Some(winner) = Arena::tournament(tournament_fighters) {
-        println!("Tournament victor: {}!", winner.get_name());
+    if let Some(tournament) = arena.tournament(tournament_fighters) {
+        println!("Tournament victor: {}!", tournament.champion.get_name());
+        println!("Match reports:");
+        for report in &tournament.matches {
+            print!("{}", report);
+        }
     }
 
     let mage_tournament = vec![
@@ -320,28 +637,568 @@ fn main() {
         GoblinMage::new("Necromancer"),
     ];
 
-    if let Some(mage_winner) = Arena::tournament(mage_tournament) {
-        println!("Mage tournament champion: {}!", mage_winner.get_name());
+    if let Some(mage_tournament) = arena.tournament(mage_tournament) {
+        println!(
+            "Mage tournament champion: {} after {} matches!",
+            mage_tournament.champion.get_name(),
+            mage_tournament.matches.len()
+        );
     }
 
     println!("\n=== COMBAT SYSTEM BATTLES ===");
 
     let mut test_warrior1 = PlayerWarrior::new("Eomer");
     let mut test_warrior2 = PlayerWarrior::new("Theoden");
-    let result1 = CombatSystem::battle(&mut test_warrior1, &mut test_warrior2);
+    let mut combat = CombatSystem::new().with_observer(ConsoleLogger);
+    let result1 = combat.battle(&mut test_warrior1, &mut test_warrior2);
     println!("Warrior vs Warrior: {:?}", result1);
 
     let mut combat_merchant = Merchant::new("Dale Merchant");
     let mut fresh_warrior = PlayerWarrior::new("Denethor");
-    let result2 = CombatSystem::battle(&mut fresh_warrior, &mut combat_merchant);
-    println!("Warrior vs Merchant: {:?}", result2);
+    let merchant_report = combat.battle_with_report(&mut fresh_warrior, &mut combat_merchant);
+    println!(
+        "Warrior vs Merchant: {:?} ({:?} after {} rounds)",
+        merchant_report.result, merchant_report.end_reason, merchant_report.rounds
+    );
+    if let Some(winner) = merchant_report.winner() {
+        println!(
+            "{} dealt {} damage and kept {} HP",
+            winner.name, winner.damage_dealt, winner.remaining_health
+        );
+    }
+
+    println!("\n=== SEEDED RANDOM COMBAT ===");
+    println!(
+        "Warrior crit chance: {:.0}%, Goblin crit chance: {:.0}%",
+        warrior.get_crit_chance() * 100.0,
+        goblin1.get_crit_chance() * 100.0
+    );
+
+    let seeded_results: Vec<BattleResult> = (0..2)
+        .map(|_| {
+            let mut seeded_combat = CombatSystem::new().with_rng(StdRng::seed_from_u64(42));
+            let mut hero = PlayerWarrior::new("Eowyn");
+            let mut orc = GoblinWarrior::new("Snaga");
+            seeded_combat.battle(&mut hero, &mut orc)
+        })
+        .collect();
+    println!(
+        "Same seed, same outcome: {:?} == {:?}",
+        seeded_results[0], seeded_results[1]
+    );
+
+    let mut random_combat = CombatSystem::new();
+    let mut random_hero = PlayerWarrior::new("Merry");
+    let mut random_orc = GoblinWarrior::new("Ugluk");
+    let random_result = random_combat.battle(&mut random_hero, &mut random_orc);
+    println!("Unseeded fight: {:?}", random_result);
+
+    let mut fixed_combat = CombatSystem::new().without_rng();
+    let mut fixed_hero = PlayerWarrior::new("Pippin");
+    let mut fixed_orc = GoblinWarrior::new("Grishnakh");
+    let fixed_report = fixed_combat.battle_with_report(&mut fixed_hero, &mut fixed_orc);
+    println!(
+        "Without an RNG: {:?}, {} misses",
+        fixed_report.result,
+        fixed_report.fighters[0].missed + fixed_report.fighters[1].missed
+    );
+
+    println!("\n=== COMBAT EVENTS ===");
+    // A closure subscriber records the fight instead of printing it
+    let recorded = Rc::new(RefCell::new(Vec::new()));
+    let recorder = Rc::clone(&recorded);
+    let mut quiet_combat = CombatSystem::new()
+        .with_observer(move |event: &CombatEvent| recorder.borrow_mut().push(event.clone()));
+    let mut quiet_hero = PlayerWarrior::new("Faramir");
+    let mut quiet_orc = GoblinWarrior::new("Lugdush");
+    quiet_combat.battle(&mut quiet_hero, &mut quiet_orc);
+
+    let events = recorded.borrow();
+    let rounds = events
+        .iter()
+        .filter_map(|event| match event {
+            CombatEvent::RoundStarted { round } => Some(*round),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+    let attacks = events
+        .iter()
+        .filter(|event| matches!(event, CombatEvent::AttackPerformed { .. }))
+        .count();
+    println!(
+        "Recorded {} events over {} rounds, {} attacks landed",
+        events.len(),
+        rounds,
+        attacks
+    );
+    if let Some(last) = events.last() {
+        println!("Final event: {:?}", last);
+    }
+    drop(events);
+
+    let mut silent_hero = PlayerWarrior::new("Hirgon");
+    let mut silent_orc = GoblinWarrior::new("Shagrat");
+    let silent_report = Arena::new().player_vs_enemy(&mut silent_hero, &mut silent_orc);
+    println!(
+        "Silent arena: {:?}, {} now has {} experience",
+        silent_report.result,
+        silent_hero.get_name(),
+        silent_hero.get_experience()
+    );
+
+    println!("\n=== STATUS EFFECTS ===");
+    let mut status_hero = PlayerWarrior::new("Pippin");
+    let mut status_orc = GoblinWarrior::new("Grishnakh's Brother");
+
+    status_hero.apply_status(StatusEffect::new(StatusKind::Shield, 20, 5));
+    status_hero.apply_status(StatusEffect::new(StatusKind::Regeneration, 4, 3));
+    status_orc.apply_status(StatusEffect::new(StatusKind::Poison, 3, 4));
+    status_orc.apply_status(StatusEffect::new(StatusKind::Poison, 3, 4)); // Stacks
+    status_orc.apply_status(StatusEffect::new(StatusKind::Bleed, 2, 2));
+    status_orc.apply_status(StatusEffect::new(StatusKind::Burn, 5, 1));
+    status_orc.apply_status(StatusEffect::new(StatusKind::Slow, 30, 3));
+    status_orc.apply_status(StatusEffect::new(StatusKind::Stun, 0, 1));
+
+    for effect in status_orc.get_status_effects().iter() {
+        println!(
+            "  {} is afflicted by {:?} x{} (potency {}, {} rounds, {:?})",
+            status_orc.get_name(),
+            effect.kind,
+            effect.stacks,
+            effect.potency,
+            effect.duration,
+            effect.kind.get_stacking_rule()
+        );
+    }
+
+    let status_result = CombatSystem::new()
+        .with_observer(ConsoleLogger)
+        .battle(&mut status_hero, &mut status_orc);
+    println!("Status battle: {:?}", status_result);
+
+    status_orc.apply_status(StatusEffect::new(StatusKind::Poison, 3, 4));
+    status_orc.apply_status(StatusEffect::new(StatusKind::Regeneration, 2, 4));
+    status_orc.revive();
+    println!(
+        "{} revived with {} HP, poisoned: {}, regenerating: {}",
+        status_orc.get_name(),
+        status_orc.get_health(),
+        status_orc.get_status_effects().has(StatusKind::Poison),
+        status_orc
+            .get_status_effects()
+            .has(StatusKind::Regeneration)
+    );
+
+    println!("\n=== MANA ECONOMY ===");
+    let mut caster = PlayerMage::new("Radagast the Brown");
+    let mut training_wyrm = DragonBoss::new("Training Wyrm");
+    println!(
+        "{}: {}/{} mana, {} regen per round, {} base damage",
+        caster.get_name(),
+        caster.get_mana(),
+        caster.get_max_mana(),
+        caster.get_mana_regen(),
+        caster.get_base_damage()
+    );
+
+    caster.set_mana(25);
+    let mana_pipeline = DamagePipeline::default();
+    for _ in 0..3 {
+        if let Some(hit) = attack_with(&mana_pipeline, &mut caster, &mut training_wyrm) {
+            println!(
+                "  {} uses {} for {} damage ({} mana left)",
+                caster.get_name(),
+                hit.attack.as_deref().unwrap_or("a plain attack"),
+                hit.final_damage,
+                caster.get_mana()
+            );
+        }
+    }
+
+    caster.restore_mana(caster.get_mana_regen());
+    println!(
+        "  After one round of regeneration: {} mana",
+        caster.get_mana()
+    );
+
+    caster.add_experience(100);
+    println!(
+        "  Level {} {}: {}/{} mana",
+        caster.get_level(),
+        caster.get_name(),
+        caster.get_mana(),
+        caster.get_max_mana()
+    );
+
+    println!("\n=== ABILITIES AND COOLDOWNS ===");
+    for hero in [&warrior as &dyn Character, &mage as &dyn Character] {
+        println!("{}'s abilities:", hero.get_name());
+        for (ability, cooldown) in hero.get_abilities().iter() {
+            println!(
+                "  {} - {} mana, {} round cooldown, targets {:?} (ready in {})",
+                ability.name, ability.mana_cost, ability.cooldown, ability.targeting, cooldown
+            );
+        }
+    }
+
+    let mut ability_warrior = PlayerWarrior::new("Beregond");
+    let mut ability_goblin = GoblinWarrior::new("Mauhur");
+    let ability_result = CombatSystem::new()
+        .with_observer(ConsoleLogger)
+        .battle(&mut ability_warrior, &mut ability_goblin);
+    println!("Ability battle: {:?}", ability_result);
+
+    let mut ability_mage = PlayerMage::new("Alatar");
+    let mut spell_target = GoblinMage::new("Gorgoroth Shaman");
+    let spell_result = CombatSystem::new()
+        .with_observer(ConsoleLogger)
+        .battle(&mut ability_mage, &mut spell_target);
+    println!("Spell battle: {:?}", spell_result);
+
+    println!("\n=== BATTLE RULES ===");
+    let mut story_combat = CombatSystem::new()
+        .with_observer(ConsoleLogger)
+        .with_config(BattleConfig::story());
+    let mut ambusher = PlayerMage::new("Mithrandir");
+    let mut ambushed = GoblinWarrior::new("Gorbag");
+    let story_report = story_combat.battle_with_report(&mut ambusher, &mut ambushed);
+    print!("Story rules: {}", story_report);
+
+    // Fire-immune dragons cannot hurt each other, so only the rules can end this
+    let mut arena_rules = CombatSystem::new().with_config(BattleConfig::arena());
+    let mut smaug_rival = DragonBoss::new("Scatha");
+    let mut ancalagon = DragonBoss::new("Ancalagon");
+    ancalagon.set_health(ancalagon.get_health() - 50);
+    let dragon_report = arena_rules.battle_with_report(&mut smaug_rival, &mut ancalagon);
+    print!("Arena rules: {}", dragon_report);
+
+    let mut stalemate = CombatSystem::new().with_config(BattleConfig {
+        max_rounds: 5,
+        first_strike: FirstStrike::Defender,
+        ..BattleConfig::default()
+    });
+    let mut glaurung = DragonBoss::new("Glaurung");
+    let mut gostir = DragonBoss::new("Gostir");
+    let stalemate_report = stalemate.battle_with_report(&mut glaurung, &mut gostir);
+    print!("Five-round stalemate: {}", stalemate_report);
+
+    let mut sparring = CombatSystem::new().with_config(BattleConfig::training());
+    let mut recruit = PlayerWarrior::new("Bergil");
+    let mut dummy = Villager::new("Straw Dummy");
+    let training_report = sparring.battle_with_report(&mut recruit, &mut dummy);
+    println!(
+        "Training: {:?} ({:?}), {} back on their feet with {} HP",
+        training_report.result,
+        training_report.end_reason,
+        dummy.get_name(),
+        dummy.get_health()
+    );
+
+    println!("\n=== INITIATIVE AND SPEED ===");
+    for fighter in [
+        &warrior as &dyn Character,
+        &mage as &dyn Character,
+        &goblin1 as &dyn Character,
+        &dragon as &dyn Character,
+    ] {
+        println!(
+            "  {}: speed {} ({} actions per round against {})",
+            fighter.get_name(),
+            fighter.get_effective_speed(),
+            initiative::actions_per_round(fighter.get_effective_speed(), dragon.get_speed()),
+            dragon.get_name()
+        );
+    }
+
+    let mut mirror_wins = [0, 0];
+    for seed in 0..20 {
+        let mut mirror_combat = CombatSystem::new().with_rng(StdRng::seed_from_u64(seed));
+        let mut left = PlayerWarrior::new("Elladan");
+        let mut right = PlayerWarrior::new("Elrohir");
+        match mirror_combat.battle(&mut left, &mut right) {
+            BattleResult::Winner1 => mirror_wins[0] += 1,
+            BattleResult::Winner2 => mirror_wins[1] += 1,
+            BattleResult::Draw => {}
+        }
+    }
+    println!(
+        "Mirror matches over 20 seeds: first argument won {}, second won {}",
+        mirror_wins[0], mirror_wins[1]
+    );
+
+    println!("\n=== PARTY VS GROUP ===");
+    let mut fellowship = vec![
+        PlayerWarrior::new("Aragorn II"),
+        PlayerWarrior::new("Boromir II"),
+        PlayerWarrior::new("Gimli II"),
+    ];
+    let mut warband = vec![
+        GoblinWarrior::new("Warband Chief"),
+        GoblinWarrior::new("Warband Grunt"),
+        GoblinWarrior::new("Warband Scout"),
+        GoblinWarrior::new("Warband Brute"),
+    ];
+    let rules = PartyRules {
+        player_targeting: TargetSelection::HighestDamage,
+        enemy_targeting: TargetSelection::LowestHealth,
+        player_victory: VictoryCondition::DefeatLeader,
+        ..PartyRules::default()
+    };
+    let party_result = arena.party_vs_group(&mut fellowship, &mut warband, &rules);
+    println!(
+        "Outcome: {:?} in {} rounds, {} heroes ({} HP) and {} enemies ({} HP) standing",
+        party_result.outcome,
+        party_result.rounds,
+        party_result.players_standing,
+        party_result.player_health,
+        party_result.enemies_standing,
+        party_result.enemy_health
+    );
+
+    println!("\n=== THREAT TABLES ===");
+    for goblin in &warband {
+        let table = goblin.get_threat_table();
+        println!(
+            "{} (aggro {}), total threat {}:",
+            goblin.get_name(),
+            goblin.get_aggro(),
+            table.total()
+        );
+        for (source, amount) in table.ranked() {
+            println!("  {}: {}", fellowship[source].get_name(), amount);
+        }
+        if let Some((leader, amount)) = table.leader() {
+            println!(
+                "  Most hated: {} ({})",
+                fellowship[leader].get_name(),
+                amount
+            );
+        }
+    }
+    println!(
+        "Gimli II threat with the chief: {}",
+        warband[0].get_threat_table().get(2)
+    );
+
+    heal_party(&mut fellowship, 30, &mut ConsoleLogger);
+    println!(
+        "Fellowship health after healing: {}",
+        party_total_health(&fellowship)
+    );
+
+    let mut shamans = vec![
+        GoblinMage::new("Cave Shaman"),
+        GoblinMage::new("Hill Shaman"),
+    ];
+    let mut scouts = vec![PlayerMage::new("Scout Mage")];
+    let holdout_rules = PartyRules {
+        player_targeting: TargetSelection::FirstAlive,
+        enemy_victory: VictoryCondition::SurviveRounds(2),
+        ..PartyRules::default()
+    };
+    let holdout = arena.party_vs_group(&mut scouts, &mut shamans, &holdout_rules);
+    println!(
+        "Holdout: {:?} after {} rounds",
+        holdout.outcome, holdout.rounds
+    );
+
+    println!("\n=== MIXED PARTIES ===");
+
+    let mut company: Vec<Combatant> = vec![
+        PlayerWarrior::new("Thorin").into(),
+        PlayerMage::new("Gandalf the Grey").into(),
+    ];
+    match CharacterBuilder::new("Beorn")
+        .with_level(2)
+        .with_base_health(140)
+        .with_ability("berserker_rage")
+        .build_actor()
+    {
+        Ok(beorn) => company.push(beorn.into()),
+        Err(error) => println!("Beorn could not join: {}", error),
+    }
+    println!("Company health: {}", party_total_health(&company));
+    if let Some(strongest) = find_strongest(&company) {
+        println!("Hardest hitter: {}", strongest.get_name());
+    }
+    if let Some(tankiest) = find_tankiest(&company) {
+        println!("Front line: {}", tankiest.get_name());
+    }
+
+    let mut trolls = vec![
+        GoblinWarrior::new("Bert"),
+        GoblinWarrior::new("Tom"),
+        GoblinMage::new("William"),
+    ];
+    let company_result = arena.party_vs_group(&mut company, &mut trolls, &PartyRules::default());
+    println!(
+        "Company: {:?} in {} rounds",
+        company_result.outcome, company_result.rounds
+    );
+    heal_party(&mut company, 40, &mut ConsoleLogger);
+    for member in &company {
+        println!(
+            "  {} level {}, {} experience",
+            member.get_name(),
+            member.get_level(),
+            member.get_experience()
+        );
+    }
+
+    let bracket: Vec<Combatant> = vec![
+        PlayerWarrior::new("Dwalin").into(),
+        PlayerMage::new("Saruman the White").into(),
+        GoblinWarrior::new("Azog the Defiler").into(),
+        DragonBoss::new("Smaug the Terrible").into(),
+    ];
+    let mut mixed_arena = Arena::with_combat(CombatSystem::new());
+    if let Some(result) = mixed_arena.tournament(bracket) {
+        println!(
+            "Mixed tournament champion: {} after {} matches",
+            result.champion.get_name(),
+            result.matches.len()
+        );
+    }
+
+    println!("\n=== CHARACTER BUILDERS ===");
+
+    let builds = [
+        CharacterBuilder::new("Hurin")
+            .with_level(5)
+            .with_strength(18)
+            .with_health(120)
+            .build_warrior()
+            .map(|hero| {
+                format!(
+                    "{:?}, {}/{} HP",
+                    hero.get_class_type(),
+                    hero.health,
+                    hero.max_health
+                )
+            }),
+        CharacterBuilder::new("Luthien")
+            .with_level(3)
+            .with_mana(160)
+            .with_power(14)
+            .with_ability("frost_nova")
+            .build_mage()
+            .map(|hero| {
+                format!(
+                    "level {}, {} mana, {} abilities",
+                    hero.get_level(),
+                    hero.get_max_mana(),
+                    hero.get_abilities().iter().count()
+                )
+            }),
+        CharacterBuilder::new("Turin")
+            .with_level(4)
+            .with_gear("flamebrand")
+            .with_gear("chainmail")
+            .build_warrior()
+            .map(|hero| {
+                format!(
+                    "{:?} {:?} attacks, {}/{} HP",
+                    hero.get_damage_type(),
+                    hero.get_attack_type(),
+                    hero.health,
+                    hero.max_health
+                )
+            }),
+        CharacterBuilder::from_preset(Preset::GoblinWarrior, "Goblin Warlord")
+            .with_threat_level(7)
+            .with_base_health(90)
+            .with_strength(12)
+            .with_ability("shield_bash")
+            .build_actor()
+            .map(|warlord| {
+                format!(
+                    "threat {}, {} HP, {} abilities",
+                    warlord.get_threat_level(),
+                    warlord.get_health(),
+                    warlord.get_abilities().iter().count()
+                )
+            }),
+        CharacterBuilder::new("Goldberry")
+            .with_importance(NPCImportance::Important)
+            .with_gold(300)
+            .with_power(12)
+            .build_actor()
+            .map(|npc| {
+                format!(
+                    "{:?} {:?}, {} gold",
+                    npc.get_importance(),
+                    npc.get_class_type(),
+                    npc.get_gold()
+                )
+            }),
+    ];
+    for build in builds {
+        match build {
+            Ok(summary) => println!("Built: {}", summary),
+            Err(error) => println!("Build failed: {}", error),
+        }
+    }
+
+    println!("Impossible combinations:");
+    let rejected = [
+        CharacterBuilder::new("Nobody")
+            .with_level(0)
+            .build_warrior()
+            .err(),
+        CharacterBuilder::new("Saruman")
+            .with_strength(20)
+            .build_mage()
+            .err(),
+        CharacterBuilder::new("Boromir")
+            .with_threat_level(5)
+            .build_warrior()
+            .err(),
+        CharacterBuilder::new("Bilbo")
+            .with_health(999)
+            .build_warrior()
+            .err(),
+        CharacterBuilder::new("Gollum")
+            .with_level(2)
+            .with_threat_level(4)
+            .build_actor()
+            .err(),
+        CharacterBuilder::from_preset(Preset::DragonBoss, "Smaug")
+            .with_importance(NPCImportance::Legendary)
+            .build_actor()
+            .err(),
+        CharacterBuilder::from_preset(Preset::Merchant, "Bill Ferny")
+            .with_mana(50)
+            .build_actor()
+            .err(),
+        CharacterBuilder::new("Fatty")
+            .with_ability("pie_throw")
+            .build_warrior()
+            .err(),
+        CharacterBuilder::new("Radagast")
+            .with_gear("flamebrand")
+            .build_mage()
+            .err(),
+        CharacterBuilder::from_preset(Preset::GoblinWarrior, "Grishnakh")
+            .with_gear("chainmail")
+            .build_actor()
+            .err(),
+    ];
+    for error in rejected.into_iter().flatten() {
+        println!("  {}", error);
+    }
 
     println!("\n=== FINAL BOSS BATTLE ===");
-    let mut final_hero = PlayerWarrior::new("Frodo");
     let mut final_boss = DragonBoss::new("Balrog");
 
-    // Give the hero some experience to make it interesting
-    final_hero.add_experience(250);
+    // Start the hero a level up to make it interesting
+    let mut final_hero = CharacterBuilder::new("Frodo")
+        .with_level(2)
+        .build_warrior()
+        .unwrap_or_else(|error| {
+            println!("Could not build the hero: {}", error);
+            PlayerWarrior::new("Frodo")
+        });
 
     println!("Epic showdown begins!");
     println!(
@@ -358,7 +1215,686 @@ fn main() {
         final_boss.is_invincible()
     );
 
-    Arena::player_vs_enemy(&mut final_hero, &mut final_boss);
+    let final_report = arena.player_vs_enemy(&mut final_hero, &mut final_boss);
+    print!("{}", final_report);
+
+    println!("\n=== SAVE AND LOAD ===");
+    let save_dir = std::env::temp_dir().join("rpg_game_saves");
+    let _ = std::fs::remove_dir_all(&save_dir); // Every demo run starts with empty slots
+    let slots = SaveSlots::new(&save_dir);
+
+    final_hero.revive();
+    print!(
+        "Save file for {}:\n{}",
+        final_hero.get_name(),
+        final_hero.to_record().to_text()
+    );
+    for (slot, player) in [(1, &final_hero as &dyn Persist), (2, &mage as &dyn Persist)] {
+        match slots.save(slot, player) {
+            Ok(()) => println!("Saved {} to slot {}", player.get_name(), slot),
+            Err(error) => println!("Could not save {}: {}", player.get_name(), error),
+        }
+    }
+
+    match slots.load(1) {
+        Ok(SavedPlayer::Warrior(loaded)) => println!(
+            "Loaded {}: level {}, {} experience, {}/{} HP, {:?}",
+            loaded.get_name(),
+            loaded.get_level(),
+            loaded.get_experience(),
+            loaded.get_health(),
+            loaded.get_calculated_max_health(),
+            loaded.get_class_type()
+        ),
+        Ok(other) => println!("Slot 1 holds a different class: {:?}", other),
+        Err(error) => println!("Could not load slot 1: {}", error),
+    }
+
+    // Damaged and outdated files are reported rather than loaded
+    let _ = std::fs::write(
+        slots.path(3),
+        "RPGSAVE 2\nkind=warrior\nname=Broken\nlevel\n",
+    );
+    let _ = std::fs::write(slots.path(4), "RPGSAVE 0\nkind=mage\nname=Ancient\n");
+    let _ = std::fs::write(
+        slots.path(6),
+        "RPGSAVE 2\nkind=mage\nname=Greedy\nhealth=999\nmax_health=82\n",
+    );
+    match slots.list() {
+        Ok(saves) => {
+            for (slot, save) in saves {
+                match save {
+                    Ok(saved) => {
+                        let player = saved.as_player();
+                        println!(
+                            "  Slot {}: {} (level {}, {} HP)",
+                            slot,
+                            player.get_name(),
+                            player.get_level(),
+                            player.get_health()
+                        );
+                    }
+                    Err(error) => println!("  Slot {}: unreadable, {}", slot, error),
+                }
+            }
+        }
+        Err(error) => println!("Could not list saves: {}", error),
+    }
+    if let Err(error) = slots.load(9) {
+        println!("Slot 9: {}", error);
+    }
+    for slot in [3, 4, 6] {
+        if let Err(error) = slots.delete(slot) {
+            println!("Could not delete slot {}: {}", slot, error);
+        }
+    }
+
+    let mut autosave_arena = Arena::with_combat(CombatSystem::new().with_observer(ConsoleLogger))
+        .with_autosave(slots.clone(), 5);
+    let mut apprentice = PlayerMage::new("Pallando");
+    let _ = apprentice.inventory.add_by_id("mana_potion", 2);
+    let _ = apprentice.inventory.add_by_id("ancient_key", 1);
+    let _ = apprentice.inventory.add_by_id("oak_staff", 1);
+    let _ = apprentice.equip("oak_staff");
+    let mut sparring_goblin = GoblinWarrior::new("Ratbag");
+    autosave_arena.player_vs_enemy(&mut apprentice, &mut sparring_goblin);
+    if let Ok(saved) = slots.load(5) {
+        let player = saved.as_player();
+        let carried: Vec<String> = player
+            .get_inventory()
+            .into_iter()
+            .flat_map(|inventory| inventory.iter())
+            .map(|stack| format!("{} x{}", stack.item.name, stack.quantity))
+            .collect();
+        let weapon = player
+            .get_equipment()
+            .and_then(|equipment| equipment.get(EquipSlot::Weapon))
+            .map_or("nothing", |item| item.name.as_str());
+        println!(
+            "Autosave holds {} at level {} with {} experience, wielding {} and carrying {}",
+            player.get_name(),
+            player.get_level(),
+            player.get_experience(),
+            weapon,
+            carried.join(", ")
+        );
+    }
+
+    println!("\n=== SAVE MIGRATIONS ===");
+    println!("Current save version: {}", SAVE_VERSION);
+    for step in MIGRATIONS {
+        println!(
+            "  v{} -> v{}: {}",
+            step.from,
+            step.from + 1,
+            step.description
+        );
+    }
+
+    // Saves written by earlier versions of the game upgrade as they load
+    let fixtures = [
+        (
+            "v1 warrior",
+            include_str!("../fixtures/saves/v1_warrior.sav"),
+        ),
+        ("v1 mage", include_str!("../fixtures/saves/v1_mage.sav")),
+        ("future", "RPGSAVE 99\nkind=warrior\n"),
+    ];
+    for (label, text) in fixtures {
+        match SaveRecord::from_text(text).and_then(|record| SavedPlayer::from_record(&record)) {
+            Ok(SavedPlayer::Mage(loaded)) => println!(
+                "  {} fixture: {} level {} with {}/{} mana, {:?}",
+                label,
+                loaded.get_name(),
+                loaded.get_level(),
+                loaded.get_mana(),
+                loaded.get_max_mana(),
+                loaded.get_class_type()
+            ),
+            Ok(saved) => {
+                let player = saved.as_player();
+                println!(
+                    "  {} fixture: {} level {}, {:?}",
+                    label,
+                    player.get_name(),
+                    player.get_level(),
+                    player.get_class_type()
+                );
+                print!("  Rewritten as:\n{}", saved.to_record().to_text());
+            }
+            Err(error) => println!("  {} fixture rejected: {}", label, error),
+        }
+    }
+
+    println!("\n=== ACTOR PRESETS ===");
+
+    for preset in Preset::ALL {
+        let template = preset.template();
+        let actor = template.spawn();
+        println!(
+            "  {:?} ({}): {} HP, {} damage, {:?}",
+            preset,
+            template.id,
+            actor.get_calculated_max_health(),
+            actor.get_calculated_damage(),
+            actor.get_character_type()
+        );
+    }
+
+    // Any actor can take the player's role, levelling from its own data
+    let mut squire = ActorTemplate {
+        character_type: CharacterType::Player { level: 1 },
+        abilities: vec!["shield_bash".to_string(), "second_wind".to_string()],
+        ..ActorTemplate::new("squire", "Squire")
+    }
+    .spawn_named("Samwise");
+    squire.add_experience(120);
+    println!(
+        "{} is now level {} with {} HP and {} experience",
+        squire.get_name(),
+        squire.get_level(),
+        squire.get_health(),
+        squire.get_experience()
+    );
+
+    println!("\n=== DATA-DRIVEN CONTENT ===");
+
+    let creature_file = concat!(env!("CARGO_MANIFEST_DIR"), "/data/creatures.txt");
+    match TemplateLibrary::load(creature_file) {
+        Ok(library) => {
+            println!("Templates loaded from data/creatures.txt:");
+            for template in library.iter() {
+                let creature = template.spawn();
+                println!(
+                    "  [{}] {}: {} HP, {} damage, {:?}",
+                    template.id,
+                    creature.get_name(),
+                    creature.get_calculated_max_health(),
+                    creature.get_calculated_damage(),
+                    creature.get_character_type()
+                );
+            }
+
+            if let (Some(mut troll), Some(shaman)) =
+                (library.spawn("cave_troll"), library.get("goblin_shaman"))
+            {
+                let mut shaman = shaman.spawn_named("Grishnak the Shaman");
+                println!(
+                    "\n{} (aggro {}) vs {} (aggro {})",
+                    troll.get_name(),
+                    troll.get_aggro(),
+                    shaman.get_name(),
+                    shaman.get_aggro()
+                );
+                let mut template_arena =
+                    Arena::with_combat(CombatSystem::new().with_observer(ConsoleLogger));
+                let template_report = template_arena.enemy_vs_enemy(&mut troll, &mut shaman);
+                print!("{}", template_report);
+            }
+
+            for id in ["blacksmith", "hermit"] {
+                if let Some(npc) = library.spawn(id) {
+                    println!("{}", npc.interact());
+                    println!(
+                        "  {:?}, trades: {}, gives quests: {}",
+                        npc.get_importance(),
+                        npc.can_trade(),
+                        npc.can_give_quests()
+                    );
+                }
+            }
+        }
+        Err(errors) => {
+            for error in errors {
+                println!("  {}", error);
+            }
+        }
+    }
+
+    let broken_templates = "\
+name = Stray Field
+[ice_wraith]
+name = Ice Wraith
+type = enemy:five
+health = 40
+damage = 12
+damage_type = Cold
+abilities = frost_nova, blizzard
+[ice_wraith]
+name = Another Wraith
+[villager]
+name = Villager
+type = npc:Minor
+health = 20
+damage = 2
+speed 4
+";
+    println!("\nBroken template file:");
+    match TemplateLibrary::parse(broken_templates) {
+        Ok(_) => println!("  Unexpectedly valid!"),
+        Err(errors) => {
+            for error in errors {
+                println!("  {}", error);
+            }
+        }
+    }
+
+    println!("\n=== CHARACTER REGISTRY ===");
+
+    let mut registry = CharacterRegistry::with_builtins();
+    match TemplateLibrary::load(creature_file) {
+        Ok(library) => {
+            if let Err(error) = registry.register_library(&library) {
+                println!("  {}", error);
+            }
+        }
+        Err(errors) => println!("  {} problems in data/creatures.txt", errors.len()),
+    }
+    if let Err(error) = registry.register("training_dummy", |name| {
+        let dummy = ActorTemplate {
+            base_health: 500,
+            passive: true,
+            ..ActorTemplate::new("training_dummy", "Training Dummy")
+        };
+        Box::new(dummy.spawn_named(name))
+    }) {
+        println!("  {}", error);
+    }
+    if let Err(error) = registry.register_template(Preset::Villager.template()) {
+        println!("Second villager rejected: {}", error);
+    }
+    println!(
+        "Known archetypes: {}",
+        registry.ids().collect::<Vec<_>>().join(", ")
+    );
+
+    let spawn_script = "\
+spawn goblin_warrior Azog
+spawn dragon_boss Smaug the Golden
+spawn cave_troll Bert
+spawn player_mage Radagast
+spawn training_dummy Scarecrow
+spawn balrog Durin's Bane";
+    let mut spawned: Vec<Box<dyn Character>> = Vec::new();
+    for command in spawn_script.lines() {
+        let Some((id, name)) = command
+            .strip_prefix("spawn ")
+            .and_then(|rest| rest.split_once(' '))
+        else {
+            continue;
+        };
+        match registry.spawn(id, name) {
+            Ok(character) => {
+                println!(
+                    "  {} -> {} ({:?}, {} HP)",
+                    id,
+                    character.get_name(),
+                    character.get_character_type(),
+                    character.get_health()
+                );
+                spawned.push(character);
+            }
+            Err(error) => println!("  {}", error),
+        }
+    }
+    let spawned_health: u32 = spawned.iter().map(|character| character.get_health()).sum();
+    println!(
+        "Spawned {} characters with {} HP between them",
+        spawned.len(),
+        spawned_health
+    );
+
+    println!("\n=== INVENTORY AND ITEMS ===");
+
+    println!("Item catalogue:");
+    for item in item_catalogue() {
+        println!(
+            "  [{}] {}: weight {}, worth {} gold, stackable: {}",
+            item.id,
+            item.name,
+            item.weight,
+            item.value,
+            item.is_stackable()
+        );
+    }
+
+    let mut pack_mule = PlayerWarrior::new("Sam");
+    let packing = [
+        ("health_potion", 3),
+        ("mana_potion", 1),
+        ("ancient_key", 1),
+        ("ancient_key", 1),
+        ("dragon_scale", 6),
+        ("lembas_bread", 1),
+    ];
+    for (id, quantity) in packing {
+        if let Err(error) = pack_mule.inventory.add_by_id(id, quantity) {
+            println!("  Can't pack {} x{}: {}", id, quantity, error);
+        }
+    }
+    let mut satchel = Inventory::new(2, 6);
+    if let Some(potion) = item_by_id("health_potion") {
+        let _ = satchel.add(potion, 2);
+    }
+    for id in ["dragon_scale", "mana_potion", "regeneration_draught"] {
+        if let Err(error) = satchel.add_by_id(id, 1) {
+            println!("  Satchel can't take {}: {}", id, error);
+        }
+    }
+    println!(
+        "{} carries {} health potions, {}/{} weight, {} free slots:",
+        pack_mule.get_name(),
+        pack_mule.inventory.count("health_potion"),
+        pack_mule.inventory.weight(),
+        pack_mule.inventory.get_max_weight(),
+        pack_mule.inventory.free_slots()
+    );
+    for stack in pack_mule.inventory.iter() {
+        println!("  {} x{}", stack.item.name, stack.quantity);
+    }
+
+    pack_mule.set_health(30);
+    for id in ["health_potion", "ancient_key", "greater_health_potion"] {
+        match use_item(&mut pack_mule, id) {
+            Ok(used) => println!(
+                "{} drinks a {}: +{} HP ({} HP), +{} mana",
+                pack_mule.get_name(),
+                used.item,
+                used.healed,
+                pack_mule.get_health(),
+                used.mana_restored
+            ),
+            Err(error) => println!("  Can't use {}: {}", id, error),
+        }
+    }
+    pack_mule.set_health(0);
+    if let Err(error) = use_item(&mut pack_mule, "health_potion") {
+        println!("  {}", error);
+    }
+
+    let mut herbalist = CharacterBuilder::new("Ioreth")
+        .with_level(3)
+        .with_health(25)
+        .with_item("mana_potion", 1)
+        .with_item("health_potion", 2)
+        .with_item("regeneration_draught", 1)
+        .build_mage()
+        .unwrap_or_else(|_| PlayerMage::new("Ioreth"));
+    herbalist.mana = 0;
+    if let Err(error) = CharacterBuilder::new("Merry")
+        .with_item("dragon_scale", 7)
+        .build_warrior()
+    {
+        println!("Build failed: {}", error);
+    }
+
+    let mut potion_goblin = GoblinMage::new("Potion Thief");
+    let potion_report = CombatSystem::new()
+        .with_observer(ConsoleLogger)
+        .battle_with_report(&mut herbalist, &mut potion_goblin);
+    print!("{}", potion_report);
+    match use_item(&mut herbalist, "regeneration_draught") {
+        Ok(used) => println!(
+            "After the fight {} drinks a {} and gains {:?}",
+            herbalist.get_name(),
+            used.item,
+            used.statuses
+        ),
+        Err(error) => println!("  {}", error),
+    }
+
+    println!("\n=== EQUIPMENT ===");
+
+    println!("Gear in the catalogue:");
+    for item in item_catalogue() {
+        if let Some(gear) = item.gear() {
+            println!(
+                "  {} ({:?}, {:?}): +{} damage, +{} HP, +{} armor, +{} magic resistance",
+                item.name,
+                gear.slot,
+                gear.class,
+                gear.bonus_damage,
+                gear.bonus_health,
+                gear.armor,
+                gear.magic_resistance
+            );
+        }
+    }
+
+    let mut aragorn = PlayerWarrior::new("Aragorn");
+    for id in [
+        "flamebrand",
+        "longbow",
+        "chainmail",
+        "frost_amulet",
+        "oak_staff",
+    ] {
+        if let Err(error) = aragorn.inventory.add_by_id(id, 1) {
+            println!("  Can't pack {}: {}", id, error);
+        }
+    }
+    let describe = |hero: &PlayerWarrior| {
+        println!(
+            "{}: {} damage ({:?}, {:?}), {}/{} HP, {} armor, {} magic resistance, frost: {:?}",
+            hero.get_name(),
+            hero.get_calculated_damage(),
+            hero.get_damage_type(),
+            hero.get_attack_type(),
+            hero.health,
+            hero.get_calculated_max_health(),
+            hero.get_armor(),
+            hero.get_magic_resistance(),
+            hero.get_affinities().get(DamageType::Frost)
+        )
+    };
+    describe(&aragorn);
+    for id in ["flamebrand", "chainmail", "frost_amulet"] {
+        if let Err(error) = aragorn.equip(id) {
+            println!("  Can't equip {}: {}", id, error);
+        }
+    }
+    describe(&aragorn);
+    if let Err(error) = aragorn.equip("longbow") {
+        println!("  Can't equip longbow: {}", error);
+    }
+    describe(&aragorn);
+    for slot in [EquipSlot::Weapon, EquipSlot::Armor, EquipSlot::Accessory] {
+        let worn = aragorn.equipment.get(slot).map(|item| item.name.as_str());
+        println!("  {:?}: {}", slot, worn.unwrap_or("nothing"));
+    }
+    println!(
+        "  Still in the pack: {}",
+        aragorn
+            .inventory
+            .iter()
+            .map(|stack| stack.item.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    println!("Gear that doesn't fit:");
+    let _ = aragorn.inventory.add_by_id("ancient_key", 1);
+    for id in ["oak_staff", "ancient_key", "silk_robe"] {
+        if let Err(error) = aragorn.equip(id) {
+            println!("  {}: {}", id, error);
+        }
+    }
+    if let Err(error) = aragorn.unequip(EquipSlot::Armor) {
+        println!("  {}", error);
+    }
+    if let Err(error) = aragorn.unequip(EquipSlot::Armor) {
+        println!("  Unequipping twice: {}", error);
+    }
+    describe(&aragorn);
+
+    let mut gandalf = PlayerMage::new("Gandalf");
+    for id in ["oak_staff", "silk_robe", "ring_of_vigor"] {
+        let _ = gandalf.inventory.add_by_id(id, 1);
+        if let Err(error) = gandalf.equip(id) {
+            println!("  Can't equip {}: {}", id, error);
+        }
+    }
+    println!(
+        "{} in full gear: {} damage, {} max HP, {} magic resistance",
+        gandalf.get_name(),
+        gandalf.get_calculated_damage(),
+        gandalf.max_health,
+        gandalf.get_magic_resistance()
+    );
+    if let Err(error) = gandalf.unequip(EquipSlot::Accessory) {
+        println!("  {}", error);
+    }
+    println!(
+        "Without the ring: {}/{} HP",
+        gandalf.health, gandalf.max_health
+    );
+
+    let _ = aragorn.equip("flamebrand");
+    let mut ice_goblin = GoblinWarrior::new("Ice Goblin");
+    ice_goblin.template.affinities = vec![(DamageType::Fire, Affinity::Weak)];
+    let gear_result = CombatSystem::new()
+        .with_observer(ConsoleLogger)
+        .battle(&mut aragorn, &mut ice_goblin);
+    println!("Geared battle: {:?}", gear_result);
+
+    println!("\n=== QUESTS ===");
+
+    let elrond = QuestGiver::new("Elrond");
+    println!("{}", elrond.interact());
+    for quest in QuestLog::offered_by(&elrond) {
+        let objectives: Vec<String> = quest.objectives.iter().map(|o| o.to_string()).collect();
+        println!(
+            "  \"{}\": {} (reward: {} exp, {} gold, {:?})",
+            quest.name,
+            objectives.join(", "),
+            quest.reward.experience,
+            quest.reward.gold,
+            quest.reward.items
+        );
+    }
+
+    let mut eowyn = CharacterBuilder::new("Eowyn")
+        .with_level(4)
+        .build_warrior()
+        .unwrap_or_else(|_| PlayerWarrior::new("Eowyn"));
+    let mut quest_log = QuestLog::new(eowyn.get_name());
+    let show_updates = |updates: Vec<QuestUpdate>| {
+        for update in updates {
+            println!(
+                "  [{}] {}: {}/{}{}",
+                update.quest,
+                update.objective,
+                update.progress,
+                update.objective.get_required(),
+                if update.completed {
+                    " - quest complete!"
+                } else {
+                    ""
+                }
+            );
+        }
+    };
+
+    let village_elder = Villager::new("Village Elder");
+    for (giver, id) in [
+        (&elrond, "goblin_menace"),
+        (&elrond, "arena_champion"),
+        (&elrond, "goblin_menace"),
+        (&elrond, "hermits_supplies"),
+        (&elrond, "slay_the_balrog"),
+        (&village_elder, "goblin_menace"),
+    ] {
+        match quest_log.accept(giver, id) {
+            Ok(quest) => println!("{} accepts \"{}\"", eowyn.get_name(), quest.name),
+            Err(error) => println!("  Can't accept {}: {}", id, error),
+        }
+    }
+    if let Err(error) = quest_log.claim("goblin_menace", &mut eowyn) {
+        println!("  {}", error);
+    }
+
+    // Only quest progress is printed, not the fights themselves
+    let mut quest_arena =
+        Arena::with_combat(CombatSystem::new().with_observer(|event: &CombatEvent| {
+            if let CombatEvent::QuestProgress { .. } = event {
+                ConsoleLogger.on_event(event);
+            }
+        }))
+        .with_quests(quest_log);
+    for name in ["Snaga", "Lugdush", "Ugluk"] {
+        let mut goblin = GoblinWarrior::new(name);
+        println!("{} fights {}", eowyn.get_name(), goblin.get_name());
+        let report = quest_arena.player_vs_enemy(&mut eowyn, &mut goblin);
+        println!("  {:?}", report.result);
+        heal(&mut eowyn, 1000);
+    }
+    let mut quest_log = quest_arena
+        .take_quests()
+        .unwrap_or_else(|| QuestLog::new(eowyn.get_name()));
+    let (greeting, updates) = quest_log.talk_to(&village_elder);
+    println!("{}", greeting);
+    show_updates(updates);
+
+    for id in ["goblin_menace", "arena_champion", "goblin_menace"] {
+        match quest_log.claim(id, &mut eowyn) {
+            Ok(reward) => println!(
+                "Claimed {}: {} exp, {} gold, {:?}",
+                id, reward.experience, reward.gold, reward.items
+            ),
+            Err(error) => println!("  {}", error),
+        }
+    }
+    println!(
+        "{} is now level {} with {} gold and {} health potions",
+        eowyn.get_name(),
+        eowyn.get_level(),
+        eowyn.get_gold(),
+        eowyn.inventory.count("health_potion")
+    );
+
+    if let Ok(library) = TemplateLibrary::load(creature_file)
+        && let Some(hermit) = library.spawn("hermit")
+    {
+        if let Err(error) = quest_log.accept(&hermit, "hermits_supplies") {
+            println!("  {}", error);
+        }
+        show_updates(quest_log.deliver(&mut eowyn, &hermit));
+        let _ = eowyn.inventory.add_by_id("health_potion", 3);
+        show_updates(quest_log.deliver(&mut eowyn, &hermit));
+
+        let _ = eowyn.inventory.add_by_id("frost_amulet", 1);
+        if let Err(error) = quest_log.claim("hermits_supplies", &mut eowyn) {
+            println!("  {}", error);
+        }
+        let _ = eowyn.inventory.remove("frost_amulet", 1);
+        if quest_log.claim("hermits_supplies", &mut eowyn).is_ok() {
+            println!(
+                "The hermit hands over a Frost Amulet ({} carried)",
+                eowyn.inventory.count("frost_amulet")
+            );
+        }
+    }
+
+    println!("Quest log:");
+    for entry in quest_log.iter() {
+        let progress: Vec<String> = entry
+            .quest
+            .objectives
+            .iter()
+            .zip(&entry.progress)
+            .map(|(objective, done)| format!("{} {}/{}", objective, done, objective.get_required()))
+            .collect();
+        println!(
+            "  {} ({:?}): {}",
+            entry.quest.name,
+            entry.status,
+            progress.join(", ")
+        );
+    }
+    println!(
+        "Goblin menace status: {:?}",
+        quest_log.get_status("goblin_menace")
+    );
 
     println!("\n=== ADDITIONAL FEATURES ===");
 
@@ -381,5 +1917,9 @@ fn main() {
     println!("\nEnemy vs Enemy Combat:");
     let mut orc1 = GoblinWarrior::new("Shagrat");
     let mut orc2 = GoblinMage::new("Gorbag");
-    Arena::enemy_vs_enemy(&mut orc1, &mut orc2);
+    let mut buffed_arena = Arena::with_combat(
+        CombatSystem::with_pipeline(balance_pipeline).with_observer(ConsoleLogger),
+    );
+    let orc_report = buffed_arena.enemy_vs_enemy(&mut orc1, &mut orc2);
+    print!("{}", orc_report);
 }
@@ -0,0 +1,325 @@
+use crate::character::{Character, CombatSystem, party_total_health};
+use crate::enemies::Enemy;
+use crate::events::CombatEvent;
+use crate::players::Player;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Side {
+    Players,
+    Enemies,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TargetSelection {
+    FirstAlive,
+    LowestHealth,
+    HighestDamage,
+}
+
+impl TargetSelection {
+    pub fn select<C: Character>(&self, candidates: &[C]) -> Option<usize> {
+        let alive = candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, candidate)| candidate.is_alive());
+        let chosen = match self {
+            TargetSelection::FirstAlive => alive.min_by_key(|(index, _)| *index),
+            TargetSelection::LowestHealth => alive.min_by_key(|(_, c)| c.get_health()),
+            TargetSelection::HighestDamage => alive.max_by_key(|(_, c)| c.get_calculated_damage()),
+        };
+        chosen.map(|(index, _)| index)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VictoryCondition {
+    DefeatAll,
+    DefeatLeader,       // The first member of the opposing group
+    SurviveRounds(u32), // Hold out with at least one member standing
+}
+
+impl VictoryCondition {
+    pub fn is_met<A, O>(&self, own: &[A], opponents: &[O], completed_rounds: u32) -> bool
+    where
+        A: Character,
+        O: Character,
+    {
+        match self {
+            VictoryCondition::DefeatAll => opponents.iter().all(|c| !c.is_alive()),
+            VictoryCondition::DefeatLeader => opponents.first().is_none_or(|c| !c.is_alive()),
+            VictoryCondition::SurviveRounds(rounds) => {
+                completed_rounds >= *rounds && own.iter().any(|c| c.is_alive())
+            }
+        }
+    }
+}
+
+// Enemies pick targets from their threat tables once anyone has drawn their attention;
+// enemy_targeting only decides their opening moves
+#[derive(Debug, Clone)]
+pub struct PartyRules {
+    pub player_targeting: TargetSelection,
+    pub enemy_targeting: TargetSelection,
+    pub player_victory: VictoryCondition,
+    pub enemy_victory: VictoryCondition,
+    pub max_rounds: u32,
+}
+
+impl Default for PartyRules {
+    fn default() -> Self {
+        PartyRules {
+            player_targeting: TargetSelection::LowestHealth,
+            enemy_targeting: TargetSelection::FirstAlive,
+            player_victory: VictoryCondition::DefeatAll,
+            enemy_victory: VictoryCondition::DefeatAll,
+            max_rounds: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PartyOutcome {
+    Won(Side),
+    Draw,
+}
+
+#[derive(Debug, Clone)]
+pub struct PartyBattleResult {
+    pub outcome: PartyOutcome,
+    pub rounds: u32,
+    pub players_standing: usize,
+    pub enemies_standing: usize,
+    pub player_health: u32,
+    pub enemy_health: u32,
+}
+
+fn decide<P, E>(
+    players: &[P],
+    enemies: &[E],
+    rules: &PartyRules,
+    completed_rounds: u32,
+) -> Option<PartyOutcome>
+where
+    P: Character,
+    E: Character,
+{
+    let players_alive = players.iter().any(|p| p.is_alive());
+    let enemies_alive = enemies.iter().any(|e| e.is_alive());
+
+    match (players_alive, enemies_alive) {
+        (false, false) => Some(PartyOutcome::Draw),
+        (false, true) => Some(PartyOutcome::Won(Side::Enemies)),
+        (true, false) => Some(PartyOutcome::Won(Side::Players)),
+        (true, true) => {
+            if rules
+                .player_victory
+                .is_met(players, enemies, completed_rounds)
+            {
+                Some(PartyOutcome::Won(Side::Players))
+            } else if rules
+                .enemy_victory
+                .is_met(enemies, players, completed_rounds)
+            {
+                Some(PartyOutcome::Won(Side::Enemies))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+impl CombatSystem {
+    pub fn party_battle<P, E>(
+        &mut self,
+        players: &mut [P],
+        enemies: &mut [E],
+        rules: &PartyRules,
+    ) -> PartyBattleResult
+    where
+        P: Player,
+        E: Enemy,
+    {
+        for enemy in enemies.iter_mut() {
+            enemy.get_threat_table_mut().clear();
+        }
+
+        let mut rounds = 0;
+        let mut outcome = decide(players, enemies, rules, rounds);
+
+        while outcome.is_none() && rounds < rules.max_rounds {
+            rounds += 1;
+            self.emit(CombatEvent::RoundStarted { round: rounds });
+
+            for member in players.iter_mut().filter(|p| p.is_alive()) {
+                self.start_round(rounds, member);
+            }
+            for member in enemies.iter_mut().filter(|e| e.is_alive()) {
+                self.start_round(rounds, member);
+            }
+
+            // Players are numbered first, then enemies; only the living roll initiative
+            let alive: Vec<usize> = (0..players.len() + enemies.len())
+                .filter(|&index| match index.checked_sub(players.len()) {
+                    None => players[index].is_alive(),
+                    Some(enemy) => enemies[enemy].is_alive(),
+                })
+                .collect();
+            let fighters: Vec<&dyn Character> = alive
+                .iter()
+                .map(|&index| match index.checked_sub(players.len()) {
+                    None => &players[index] as &dyn Character,
+                    Some(enemy) => &enemies[enemy] as &dyn Character,
+                })
+                .collect();
+            let order = self.turn_order(rounds, &fighters);
+
+            for slot in order {
+                if decide(players, enemies, rules, rounds - 1).is_some() {
+                    break;
+                }
+
+                match alive[slot].checked_sub(players.len()) {
+                    None => {
+                        let actor = alive[slot];
+                        let Some(target) = rules.player_targeting.select(enemies) else {
+                            continue;
+                        };
+                        if !players[actor].is_alive() {
+                            continue;
+                        }
+                        let turn =
+                            self.take_turn(rounds, &mut players[actor], &mut enemies[target]);
+
+                        enemies[target]
+                            .get_threat_table_mut()
+                            .add(actor, turn.damage_dealt + turn.taunt);
+                        for enemy in enemies.iter_mut().filter(|e| e.is_alive()) {
+                            enemy
+                                .get_threat_table_mut()
+                                .add_healing(actor, turn.healing_done);
+                        }
+                    }
+                    Some(actor) => {
+                        let enemy = &enemies[actor];
+                        let Some(target) = enemy
+                            .get_threat_table()
+                            .select_target(enemy.get_aggro(), players)
+                            .or_else(|| rules.enemy_targeting.select(players))
+                        else {
+                            continue;
+                        };
+                        if enemies[actor].is_alive() {
+                            self.take_turn(rounds, &mut enemies[actor], &mut players[target]);
+                        }
+                    }
+                }
+            }
+
+            for member in players.iter_mut().filter(|p| p.is_alive()) {
+                self.end_round(rounds, member);
+            }
+            for member in enemies.iter_mut().filter(|e| e.is_alive()) {
+                self.end_round(rounds, member);
+            }
+
+            outcome = decide(players, enemies, rules, rounds);
+        }
+
+        for member in players.iter_mut() {
+            self.end_battle(member);
+        }
+        for member in enemies.iter_mut() {
+            self.end_battle(member);
+        }
+
+        let outcome = outcome.unwrap_or(PartyOutcome::Draw);
+        self.emit(CombatEvent::PartyBattleEnded { outcome, rounds });
+
+        PartyBattleResult {
+            outcome,
+            rounds,
+            players_standing: players.iter().filter(|p| p.is_alive()).count(),
+            enemies_standing: enemies.iter().filter(|e| e.is_alive()).count(),
+            player_health: party_total_health(players),
+            enemy_health: party_total_health(enemies),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enemies::{DragonBoss, GoblinWarrior};
+    use crate::players::PlayerWarrior;
+
+    #[test]
+    fn targeting_skips_the_fallen() {
+        let mut party = vec![
+            PlayerWarrior::new("Boromir"),
+            PlayerWarrior::new("Faramir"),
+            PlayerWarrior::new("Denethor"),
+        ];
+        party[0].set_health(0);
+        party[1].set_health(10);
+
+        assert_eq!(TargetSelection::FirstAlive.select(&party), Some(1));
+        assert_eq!(TargetSelection::LowestHealth.select(&party), Some(1));
+        party[1].set_health(0);
+        party[2].set_health(0);
+        assert_eq!(TargetSelection::FirstAlive.select(&party), None);
+    }
+
+    #[test]
+    fn defeating_the_leader_ends_the_fight() {
+        let mut players = vec![PlayerWarrior::new("Boromir"), PlayerWarrior::new("Faramir")];
+        let mut enemies = vec![GoblinWarrior::new("Azog"), GoblinWarrior::new("Bolg")];
+        enemies[0].set_health(1);
+        let rules = PartyRules {
+            player_victory: VictoryCondition::DefeatLeader,
+            ..PartyRules::default()
+        };
+
+        let result =
+            CombatSystem::new()
+                .without_rng()
+                .party_battle(&mut players, &mut enemies, &rules);
+        assert_eq!(result.outcome, PartyOutcome::Won(Side::Players));
+        assert!(!enemies[0].is_alive());
+        assert_eq!(result.enemies_standing, 1);
+    }
+
+    #[test]
+    fn surviving_enough_rounds_wins() {
+        let mut players = vec![PlayerWarrior::new("Boromir")];
+        let mut enemies = vec![DragonBoss::new("Smaug")];
+        let rules = PartyRules {
+            enemy_victory: VictoryCondition::SurviveRounds(2),
+            ..PartyRules::default()
+        };
+
+        let result =
+            CombatSystem::new()
+                .without_rng()
+                .party_battle(&mut players, &mut enemies, &rules);
+        assert_eq!(result.outcome, PartyOutcome::Won(Side::Enemies));
+        assert_eq!(result.rounds, 2);
+        assert_eq!(result.players_standing, 1);
+    }
+
+    #[test]
+    fn the_round_limit_is_a_draw() {
+        let mut players = vec![PlayerWarrior::new("Boromir")];
+        let mut enemies = vec![DragonBoss::new("Smaug")];
+        let rules = PartyRules {
+            max_rounds: 1,
+            ..PartyRules::default()
+        };
+
+        let result =
+            CombatSystem::new()
+                .without_rng()
+                .party_battle(&mut players, &mut enemies, &rules);
+        assert_eq!(result.outcome, PartyOutcome::Draw);
+        assert_eq!(result.rounds, 1);
+    }
+}
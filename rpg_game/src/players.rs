@@ -1,13 +1,24 @@
+use crate::abilities::{Abilities, mage_starter_kit, warrior_starter_kit};
 use crate::character::{Character, CharacterType};
 use crate::classes::{
     AttackType, ClassType, DamageType, Mage as MageClass, Warrior as WarriorClass,
 };
+use crate::damage::AttackProfile;
+use crate::equipment::{EquipError, EquipSlot, Equipment};
+use crate::items::Inventory;
+use crate::save::Persist;
+use crate::status::StatusEffects;
 
 pub trait Player: Character {
     fn level_up(&mut self);
     fn get_experience(&self) -> u32;
     fn get_level(&self) -> u32;
     fn add_experience(&mut self, exp: u32);
+
+    // Players that can be written to a save slot
+    fn as_persist(&self) -> Option<&dyn Persist> {
+        None
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -16,9 +27,14 @@ pub struct PlayerWarrior {
     pub health: u32,
     pub max_health: u32,
     pub experience: u32,
+    pub gold: u32,
     pub level: u32,
     pub class: ClassType,
     pub character_type: CharacterType,
+    pub status_effects: StatusEffects,
+    pub abilities: Abilities,
+    pub inventory: Inventory,
+    pub equipment: Equipment,
 }
 
 impl Character for PlayerWarrior {
@@ -34,15 +50,22 @@ impl Character for PlayerWarrior {
             health: max_health,
             max_health,
             experience: 0,
+            gold: 0,
             level,
             class,
             character_type,
+            status_effects: StatusEffects::default(),
+            abilities: Abilities::from_kit(warrior_starter_kit()),
+            inventory: Inventory::default(),
+            equipment: Equipment::default(),
         }
     }
 
     fn revive(&mut self) {
         if self.health == 0 {
             self.health = self.get_calculated_max_health();
+            self.status_effects.on_revive();
+            self.abilities.reset_cooldowns();
         }
     }
 
@@ -66,20 +89,80 @@ impl Character for PlayerWarrior {
         &self.character_type
     }
 
+    fn get_status_effects(&self) -> &StatusEffects {
+        &self.status_effects
+    }
+
+    fn get_status_effects_mut(&mut self) -> &mut StatusEffects {
+        &mut self.status_effects
+    }
+
+    fn get_abilities(&self) -> &Abilities {
+        &self.abilities
+    }
+
+    fn get_abilities_mut(&mut self) -> &mut Abilities {
+        &mut self.abilities
+    }
+
+    fn get_inventory(&self) -> Option<&Inventory> {
+        Some(&self.inventory)
+    }
+
+    fn get_inventory_mut(&mut self) -> Option<&mut Inventory> {
+        Some(&mut self.inventory)
+    }
+
+    fn get_equipment(&self) -> Option<&Equipment> {
+        Some(&self.equipment)
+    }
+
+    fn get_gold(&self) -> u32 {
+        self.gold
+    }
+
+    fn set_gold(&mut self, gold: u32) {
+        self.gold = gold;
+    }
+
     fn get_base_health(&self) -> u32 {
-        100
+        100 + self.equipment.get_bonus_health()
     }
 
     fn get_damage_type(&self) -> DamageType {
-        DamageType::Physical
+        self.equipment
+            .get_damage_type()
+            .unwrap_or(DamageType::Physical)
     }
 
     fn get_attack_type(&self) -> AttackType {
-        AttackType::Melee
+        self.equipment
+            .get_attack_type()
+            .unwrap_or(AttackType::Melee)
     }
 
     fn get_base_damage(&self) -> u32 {
-        25
+        25 + self.equipment.get_bonus_damage()
+    }
+}
+
+impl PlayerWarrior {
+    pub fn equip(&mut self, id: &str) -> Result<(), EquipError> {
+        self.equipment.equip(id, &self.class, &mut self.inventory)?;
+        self.refresh_max_health();
+        Ok(())
+    }
+
+    pub fn unequip(&mut self, slot: EquipSlot) -> Result<(), EquipError> {
+        self.equipment.unequip(slot, &mut self.inventory)?;
+        self.refresh_max_health();
+        Ok(())
+    }
+
+    // Gear raises max health without healing, and losing it caps current health
+    pub(crate) fn refresh_max_health(&mut self) {
+        self.max_health = self.get_calculated_max_health();
+        self.health = self.health.min(self.max_health);
     }
 }
 
@@ -100,11 +183,15 @@ impl Player for PlayerWarrior {
         self.level
     }
 
+    fn as_persist(&self) -> Option<&dyn Persist> {
+        Some(self)
+    }
+
     fn add_experience(&mut self, exp: u32) {
         self.experience += exp;
-        let exp_needed = self.level * 100; // 100 exp per level
-        if self.experience >= exp_needed {
-            self.experience -= exp_needed;
+        // A big reward can be worth several levels
+        while self.experience >= self.level * 100 {
+            self.experience -= self.level * 100; // 100 exp per level
             self.level_up();
         }
     }
@@ -118,9 +205,14 @@ pub struct PlayerMage {
     pub mana: u32,
     pub max_mana: u32,
     pub experience: u32,
+    pub gold: u32,
     pub level: u32,
     pub class: ClassType,
     pub character_type: CharacterType,
+    pub status_effects: StatusEffects,
+    pub abilities: Abilities,
+    pub inventory: Inventory,
+    pub equipment: Equipment,
 }
 
 impl Character for PlayerMage {
@@ -130,23 +222,31 @@ impl Character for PlayerMage {
         let class = ClassType::Mage(MageClass::new());
         let base_health = 75;
         let max_health = (base_health as f32 * character_type.get_health_coefficient()) as u32;
+        let max_mana = class.get_mana().unwrap_or(0);
 
         PlayerMage {
             name: name.to_string(),
             health: max_health,
             max_health,
-            mana: 100,
-            max_mana: 100,
+            mana: max_mana,
+            max_mana,
             experience: 0,
+            gold: 0,
             level,
             class,
             character_type,
+            status_effects: StatusEffects::default(),
+            abilities: Abilities::from_kit(mage_starter_kit()),
+            inventory: Inventory::default(),
+            equipment: Equipment::default(),
         }
     }
 
     fn revive(&mut self) {
         if self.health == 0 {
             self.health = self.get_calculated_max_health();
+            self.status_effects.on_revive();
+            self.abilities.reset_cooldowns();
             self.mana = self.max_mana;
         }
     }
@@ -171,20 +271,110 @@ impl Character for PlayerMage {
         &self.character_type
     }
 
+    fn get_status_effects(&self) -> &StatusEffects {
+        &self.status_effects
+    }
+
+    fn get_status_effects_mut(&mut self) -> &mut StatusEffects {
+        &mut self.status_effects
+    }
+
+    fn get_abilities(&self) -> &Abilities {
+        &self.abilities
+    }
+
+    fn get_abilities_mut(&mut self) -> &mut Abilities {
+        &mut self.abilities
+    }
+
+    fn get_inventory(&self) -> Option<&Inventory> {
+        Some(&self.inventory)
+    }
+
+    fn get_inventory_mut(&mut self) -> Option<&mut Inventory> {
+        Some(&mut self.inventory)
+    }
+
+    fn get_equipment(&self) -> Option<&Equipment> {
+        Some(&self.equipment)
+    }
+
+    fn get_gold(&self) -> u32 {
+        self.gold
+    }
+
+    fn set_gold(&mut self, gold: u32) {
+        self.gold = gold;
+    }
+
     fn get_base_health(&self) -> u32 {
-        75
+        75 + self.equipment.get_bonus_health()
     }
 
     fn get_damage_type(&self) -> DamageType {
-        DamageType::Magical
+        self.equipment
+            .get_damage_type()
+            .unwrap_or(DamageType::Magical)
     }
 
     fn get_attack_type(&self) -> AttackType {
-        AttackType::Ranged
+        self.equipment
+            .get_attack_type()
+            .unwrap_or(AttackType::Ranged)
     }
 
     fn get_base_damage(&self) -> u32 {
-        30
+        20 + self.class.get_power().unwrap_or(0) + self.equipment.get_bonus_damage()
+    }
+
+    fn get_mana(&self) -> u32 {
+        self.mana
+    }
+
+    fn get_max_mana(&self) -> u32 {
+        self.max_mana
+    }
+
+    fn set_mana(&mut self, mana: u32) {
+        self.mana = mana.min(self.max_mana);
+    }
+
+    fn get_mana_regen(&self) -> u32 {
+        self.class.get_power().unwrap_or(0) / 2
+    }
+
+    fn get_attack(&self) -> AttackProfile {
+        AttackProfile {
+            name: Some("Arcane Bolt".to_string()),
+            damage_type: self.get_damage_type(),
+            attack_type: self.get_attack_type(),
+            base_damage: self.get_base_damage(),
+            mana_cost: 10,
+        }
+    }
+}
+
+impl PlayerMage {
+    pub fn equip(&mut self, id: &str) -> Result<(), EquipError> {
+        self.equipment.equip(id, &self.class, &mut self.inventory)?;
+        self.refresh_max_health();
+        Ok(())
+    }
+
+    pub fn unequip(&mut self, slot: EquipSlot) -> Result<(), EquipError> {
+        self.equipment.unequip(slot, &mut self.inventory)?;
+        self.refresh_max_health();
+        Ok(())
+    }
+
+    pub(crate) fn refresh_max_health(&mut self) {
+        self.max_health = self.get_calculated_max_health();
+        self.health = self.health.min(self.max_health);
+    }
+
+    // Class mana pool plus 10 for every level above 1
+    pub(crate) fn calculate_max_mana(&self) -> u32 {
+        self.class.get_mana().unwrap_or(0) + (self.level - 1) * 10
     }
 }
 
@@ -195,7 +385,7 @@ impl Player for PlayerMage {
         let new_max_health = self.get_calculated_max_health();
         self.max_health = new_max_health;
         self.health = new_max_health;
-        self.max_mana += 10; // Increase mana with level
+        self.max_mana = self.calculate_max_mana();
         self.mana = self.max_mana;
     }
 
@@ -207,11 +397,14 @@ impl Player for PlayerMage {
         self.level
     }
 
+    fn as_persist(&self) -> Option<&dyn Persist> {
+        Some(self)
+    }
+
     fn add_experience(&mut self, exp: u32) {
         self.experience += exp;
-        let exp_needed = self.level * 100;
-        if self.experience >= exp_needed {
-            self.experience -= exp_needed;
+        while self.experience >= self.level * 100 {
+            self.experience -= self.level * 100;
             self.level_up();
         }
     }
@@ -0,0 +1,549 @@
+use std::fmt;
+
+use crate::actor::Actor;
+use crate::character::Character;
+use crate::enemies::Enemy;
+use crate::items::InventoryError;
+use crate::npcs::NPC;
+use crate::players::Player;
+
+// Characters are matched by template ID, so any goblin warrior counts whatever
+// its name
+#[derive(Debug, Clone, PartialEq)]
+pub enum Objective {
+    Defeat {
+        enemy: String,
+        count: u32,
+    },
+    TalkTo {
+        npc: String,
+    },
+    Deliver {
+        item: String,
+        count: u32,
+        to: String,
+    },
+    WinArenaFights {
+        count: u32,
+    },
+}
+
+impl Objective {
+    pub fn get_required(&self) -> u32 {
+        match self {
+            Objective::Defeat { count, .. }
+            | Objective::Deliver { count, .. }
+            | Objective::WinArenaFights { count } => *count,
+            Objective::TalkTo { .. } => 1,
+        }
+    }
+}
+
+impl fmt::Display for Objective {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Objective::Defeat { enemy, count } => write!(f, "defeat {} {}", count, enemy),
+            Objective::TalkTo { npc } => write!(f, "talk to {}", npc),
+            Objective::Deliver { item, count, to } => {
+                write!(f, "deliver {} {} to {}", count, item, to)
+            }
+            Objective::WinArenaFights { count } => write!(f, "win {} arena fights", count),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Reward {
+    pub experience: u32,
+    pub gold: u32,
+    pub items: Vec<(String, u32)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Quest {
+    pub id: String,
+    pub name: String,
+    pub objectives: Vec<Objective>,
+    pub reward: Reward,
+}
+
+pub fn quest_catalogue() -> Vec<Quest> {
+    vec![
+        Quest {
+            id: "goblin_menace".to_string(),
+            name: "The Goblin Menace".to_string(),
+            objectives: vec![
+                Objective::Defeat {
+                    enemy: "goblin_warrior".to_string(),
+                    count: 2,
+                },
+                Objective::TalkTo {
+                    npc: "villager".to_string(),
+                },
+            ],
+            reward: Reward {
+                experience: 150,
+                gold: 100,
+                items: vec![("health_potion".to_string(), 2)],
+            },
+        },
+        Quest {
+            id: "arena_champion".to_string(),
+            name: "Champion of the Arena".to_string(),
+            objectives: vec![Objective::WinArenaFights { count: 3 }],
+            reward: Reward {
+                experience: 300,
+                gold: 250,
+                items: vec![("ring_of_vigor".to_string(), 1)],
+            },
+        },
+        Quest {
+            id: "hermits_supplies".to_string(),
+            name: "Supplies for the Hermit".to_string(),
+            objectives: vec![Objective::Deliver {
+                item: "health_potion".to_string(),
+                count: 3,
+                to: "hermit".to_string(),
+            }],
+            reward: Reward {
+                experience: 80,
+                gold: 0,
+                items: vec![("frost_amulet".to_string(), 1)],
+            },
+        },
+        Quest {
+            id: "dragon_slayer".to_string(),
+            name: "Bane of the Dragon".to_string(),
+            objectives: vec![Objective::Defeat {
+                enemy: "dragon_boss".to_string(),
+                count: 1,
+            }],
+            reward: Reward {
+                experience: 1000,
+                gold: 500,
+                items: vec![("dragon_scale".to_string(), 1)],
+            },
+        },
+    ]
+}
+
+pub fn quest_by_id(id: &str) -> Option<Quest> {
+    quest_catalogue().into_iter().find(|quest| quest.id == id)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuestError {
+    NotAQuestGiver(String),
+    NotOffered { giver: String, quest: String },
+    UnknownQuest(String),
+    AlreadyTaken(String),
+    NotTaken(String),
+    NotComplete(String),
+    AlreadyClaimed(String),
+    NoRoom(InventoryError), // The reward items don't fit
+}
+
+impl fmt::Display for QuestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QuestError::NotAQuestGiver(name) => write!(f, "{} has no quests to give", name),
+            QuestError::NotOffered { giver, quest } => {
+                write!(f, "{} doesn't offer `{}`", giver, quest)
+            }
+            QuestError::UnknownQuest(id) => write!(f, "unknown quest `{}`", id),
+            QuestError::AlreadyTaken(name) => write!(f, "\"{}\" is already in the log", name),
+            QuestError::NotTaken(id) => write!(f, "`{}` isn't in the log", id),
+            QuestError::NotComplete(name) => write!(f, "\"{}\" isn't finished yet", name),
+            QuestError::AlreadyClaimed(name) => {
+                write!(f, "the reward for \"{}\" was already claimed", name)
+            }
+            QuestError::NoRoom(error) => write!(f, "can't take the reward: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for QuestError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuestStatus {
+    Active,
+    Completed, // Every objective done, reward not yet claimed
+    Claimed,
+}
+
+#[derive(Debug, Clone)]
+pub struct QuestProgress {
+    pub quest: Quest,
+    pub progress: Vec<u32>, // One count per objective
+    pub status: QuestStatus,
+}
+
+// Reported whenever a hook moves an objective forward
+#[derive(Debug, Clone)]
+pub struct QuestUpdate {
+    pub quest: String,
+    pub objective: Objective,
+    pub progress: u32,
+    pub completed: bool, // The whole quest, not just this objective
+}
+
+// One player's quests. An arena holding the log and talk_to feed the record_* hooks;
+// other sources of progress can call them directly.
+#[derive(Debug, Clone)]
+pub struct QuestLog {
+    owner: String,
+    quests: Vec<QuestProgress>,
+}
+
+impl QuestLog {
+    pub fn new(owner: &str) -> Self {
+        QuestLog {
+            owner: owner.to_string(),
+            quests: Vec::new(),
+        }
+    }
+
+    pub fn get_owner(&self) -> &str {
+        &self.owner
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &QuestProgress> {
+        self.quests.iter()
+    }
+
+    pub fn get_status(&self, id: &str) -> Option<QuestStatus> {
+        self.find(id).map(|entry| entry.status)
+    }
+
+    fn find(&self, id: &str) -> Option<&QuestProgress> {
+        self.quests.iter().find(|entry| entry.quest.id == id)
+    }
+
+    // The quests a giver's template lists, if they give quests at all
+    pub fn offered_by(giver: &Actor) -> Vec<Quest> {
+        if !giver.can_give_quests() {
+            return Vec::new();
+        }
+        giver
+            .template
+            .quests
+            .iter()
+            .filter_map(|id| quest_by_id(id))
+            .collect()
+    }
+
+    pub fn accept(&mut self, giver: &Actor, id: &str) -> Result<&Quest, QuestError> {
+        if !giver.can_give_quests() {
+            return Err(QuestError::NotAQuestGiver(giver.get_name().to_string()));
+        }
+        let quest = quest_by_id(id).ok_or_else(|| QuestError::UnknownQuest(id.to_string()))?;
+        if !giver.template.quests.iter().any(|offered| offered == id) {
+            return Err(QuestError::NotOffered {
+                giver: giver.get_name().to_string(),
+                quest: id.to_string(),
+            });
+        }
+        if self.find(id).is_some() {
+            return Err(QuestError::AlreadyTaken(quest.name));
+        }
+
+        self.quests.push(QuestProgress {
+            progress: vec![0; quest.objectives.len()],
+            quest,
+            status: QuestStatus::Active,
+        });
+        Ok(&self.quests[self.quests.len() - 1].quest)
+    }
+
+    // Adds to every active objective `matches` picks, up to what it needs. It's given
+    // the quest ID and the objective's index alongside the objective itself.
+    fn advance<F>(&mut self, mut matches: F) -> Vec<QuestUpdate>
+    where
+        F: FnMut(&str, usize, &Objective) -> Option<u32>,
+    {
+        let mut updates = Vec::new();
+        for entry in &mut self.quests {
+            if entry.status != QuestStatus::Active {
+                continue;
+            }
+            let first_update = updates.len();
+            let objectives = entry.quest.objectives.iter().zip(&mut entry.progress);
+            for (index, (objective, progress)) in objectives.enumerate() {
+                let required = objective.get_required();
+                if *progress >= required {
+                    continue;
+                }
+                let amount = matches(&entry.quest.id, index, objective);
+                if let Some(amount) = amount.filter(|&amount| amount > 0) {
+                    *progress = (*progress + amount).min(required);
+                    updates.push(QuestUpdate {
+                        quest: entry.quest.name.clone(),
+                        objective: objective.clone(),
+                        progress: *progress,
+                        completed: false,
+                    });
+                }
+            }
+
+            let done = entry
+                .quest
+                .objectives
+                .iter()
+                .zip(&entry.progress)
+                .all(|(objective, progress)| *progress >= objective.get_required());
+            if done {
+                entry.status = QuestStatus::Completed;
+                if let Some(update) = updates[first_update..].last_mut() {
+                    update.completed = true;
+                }
+            }
+        }
+        updates
+    }
+
+    pub fn record_defeat(&mut self, enemy: &dyn Enemy) -> Vec<QuestUpdate> {
+        if enemy.is_alive() {
+            return Vec::new();
+        }
+        self.advance(|_, _, objective| match objective {
+            Objective::Defeat { enemy: id, .. } if id == enemy.get_id() => Some(1),
+            _ => None,
+        })
+    }
+
+    pub fn record_win(&mut self) -> Vec<QuestUpdate> {
+        self.advance(|_, _, objective| match objective {
+            Objective::WinArenaFights { .. } => Some(1),
+            _ => None,
+        })
+    }
+
+    pub fn record_talk(&mut self, npc: &Actor) -> Vec<QuestUpdate> {
+        self.advance(|_, _, objective| match objective {
+            Objective::TalkTo { npc: id } if *id == npc.template.id => Some(1),
+            _ => None,
+        })
+    }
+
+    // Talks to the NPC and counts the conversation towards the log's quests
+    pub fn talk_to(&mut self, npc: &Actor) -> (String, Vec<QuestUpdate>) {
+        (npc.interact(), self.record_talk(npc))
+    }
+
+    // Hands over whatever the player carries towards deliveries for this NPC
+    pub fn deliver(&mut self, player: &mut dyn Character, npc: &Actor) -> Vec<QuestUpdate> {
+        let Some(inventory) = player.get_inventory_mut() else {
+            return Vec::new();
+        };
+        let mut handed_over: Vec<(String, usize, u32)> = Vec::new();
+        for entry in self
+            .quests
+            .iter()
+            .filter(|entry| entry.status == QuestStatus::Active)
+        {
+            let objectives = entry.quest.objectives.iter().zip(&entry.progress);
+            for (index, (objective, progress)) in objectives.enumerate() {
+                if let Objective::Deliver { item, count, to } = objective
+                    && *to == npc.template.id
+                {
+                    let amount = inventory.count(item).min(count - progress);
+                    if amount > 0 && inventory.remove(item, amount).is_ok() {
+                        handed_over.push((entry.quest.id.clone(), index, amount));
+                    }
+                }
+            }
+        }
+
+        self.advance(|quest, index, _| {
+            handed_over
+                .iter()
+                .find(|(id, delivered, _)| id == quest && *delivered == index)
+                .map(|(_, _, amount)| *amount)
+        })
+    }
+
+    // Pays out a completed quest. Nothing is given unless all reward items fit.
+    pub fn claim(&mut self, id: &str, player: &mut dyn Player) -> Result<Reward, QuestError> {
+        let entry = self
+            .quests
+            .iter_mut()
+            .find(|entry| entry.quest.id == id)
+            .ok_or_else(|| QuestError::NotTaken(id.to_string()))?;
+        match entry.status {
+            QuestStatus::Active => return Err(QuestError::NotComplete(entry.quest.name.clone())),
+            QuestStatus::Claimed => {
+                return Err(QuestError::AlreadyClaimed(entry.quest.name.clone()));
+            }
+            QuestStatus::Completed => {}
+        }
+
+        let reward = entry.quest.reward.clone();
+        if let Some(inventory) = player.get_inventory_mut() {
+            let mut pack = inventory.clone();
+            for (item, count) in &reward.items {
+                pack.add_by_id(item, *count).map_err(QuestError::NoRoom)?;
+            }
+            *inventory = pack;
+        }
+        player.earn_gold(reward.gold);
+        player.add_experience(reward.experience);
+        entry.status = QuestStatus::Claimed;
+        Ok(reward)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actor::Preset;
+    use crate::items::Inventory;
+    use crate::players::PlayerWarrior;
+
+    // Accepts the Goblin Menace and does everything it asks
+    fn finished_goblin_menace() -> QuestLog {
+        let mut log = QuestLog::new("Eowyn");
+        log.accept(&Preset::QuestGiver.spawn("Elrond"), "goblin_menace")
+            .unwrap();
+        for _ in 0..2 {
+            let mut goblin = Preset::GoblinWarrior.spawn("Snaga");
+            goblin.set_health(0);
+            log.record_defeat(&goblin);
+        }
+        log.record_talk(&Preset::Villager.spawn("Rosie"));
+        log
+    }
+
+    #[test]
+    fn only_offered_quests_can_be_accepted() {
+        let mut log = QuestLog::new("Eowyn");
+        let elrond = Preset::QuestGiver.spawn("Elrond");
+        assert!(matches!(
+            log.accept(&elrond, "hermits_supplies"),
+            Err(QuestError::NotOffered { .. })
+        ));
+        assert!(matches!(
+            log.accept(&Preset::Villager.spawn("Rosie"), "goblin_menace"),
+            Err(QuestError::NotAQuestGiver(_))
+        ));
+        log.accept(&elrond, "goblin_menace").unwrap();
+        assert!(matches!(
+            log.accept(&elrond, "goblin_menace"),
+            Err(QuestError::AlreadyTaken(_))
+        ));
+    }
+
+    #[test]
+    fn the_legendary_npc_offers_a_quest() {
+        let bombadil = Preset::LegendaryNPC.spawn("Tom Bombadil");
+        assert!(!QuestLog::offered_by(&bombadil).is_empty());
+        let mut log = QuestLog::new("Eowyn");
+        assert!(log.accept(&bombadil, "dragon_slayer").is_ok());
+    }
+
+    #[test]
+    fn living_enemies_and_extra_kills_dont_count() {
+        let mut log = QuestLog::new("Eowyn");
+        log.accept(&Preset::QuestGiver.spawn("Elrond"), "goblin_menace")
+            .unwrap();
+        let mut goblin = Preset::GoblinWarrior.spawn("Snaga");
+        assert!(log.record_defeat(&goblin).is_empty());
+
+        goblin.set_health(0);
+        for _ in 0..3 {
+            log.record_defeat(&goblin);
+        }
+        let entry = log.iter().next().unwrap();
+        assert_eq!(entry.progress, vec![2, 0]);
+        assert_eq!(entry.status, QuestStatus::Active);
+    }
+
+    #[test]
+    fn claiming_pays_out_once() {
+        let mut log = finished_goblin_menace();
+        let mut eowyn = PlayerWarrior::new("Eowyn");
+        assert_eq!(
+            log.get_status("goblin_menace"),
+            Some(QuestStatus::Completed)
+        );
+
+        let reward = log.claim("goblin_menace", &mut eowyn).unwrap();
+        assert_eq!(eowyn.gold, reward.gold);
+        assert_eq!(eowyn.inventory.count("health_potion"), 2);
+        assert_eq!(eowyn.get_level(), 2); // 150 experience is a level and a half
+        assert!(matches!(
+            log.claim("goblin_menace", &mut eowyn),
+            Err(QuestError::AlreadyClaimed(_))
+        ));
+        assert_eq!(eowyn.gold, reward.gold);
+    }
+
+    #[test]
+    fn unfinished_or_unknown_quests_cant_be_claimed() {
+        let mut log = QuestLog::new("Eowyn");
+        let mut eowyn = PlayerWarrior::new("Eowyn");
+        assert!(matches!(
+            log.claim("goblin_menace", &mut eowyn),
+            Err(QuestError::NotTaken(_))
+        ));
+        log.accept(&Preset::QuestGiver.spawn("Elrond"), "goblin_menace")
+            .unwrap();
+        assert!(matches!(
+            log.claim("goblin_menace", &mut eowyn),
+            Err(QuestError::NotComplete(_))
+        ));
+    }
+
+    #[test]
+    fn a_full_pack_keeps_the_whole_reward_waiting() {
+        let mut log = finished_goblin_menace();
+        let mut eowyn = PlayerWarrior::new("Eowyn");
+        eowyn.inventory = Inventory::new(1, 30);
+        eowyn.inventory.add_by_id("ancient_key", 1).unwrap();
+
+        assert!(matches!(
+            log.claim("goblin_menace", &mut eowyn),
+            Err(QuestError::NoRoom(_))
+        ));
+        assert_eq!((eowyn.gold, eowyn.get_experience()), (0, 0));
+        assert_eq!(
+            log.get_status("goblin_menace"),
+            Some(QuestStatus::Completed)
+        );
+
+        eowyn.inventory = Inventory::default();
+        assert!(log.claim("goblin_menace", &mut eowyn).is_ok());
+    }
+
+    #[test]
+    fn deliveries_take_only_what_is_needed() {
+        let mut log = QuestLog::new("Eowyn");
+        let mut hermit_template = Preset::Villager.template();
+        hermit_template.id = "hermit".to_string();
+        hermit_template.gives_quests = true;
+        hermit_template.quests = vec!["hermits_supplies".to_string()];
+        let hermit = hermit_template.spawn();
+        log.accept(&hermit, "hermits_supplies").unwrap();
+
+        let mut eowyn = PlayerWarrior::new("Eowyn");
+        eowyn.inventory.add_by_id("health_potion", 5).unwrap();
+        let updates = log.deliver(&mut eowyn, &hermit);
+        assert!(updates[0].completed);
+        assert_eq!(eowyn.inventory.count("health_potion"), 2);
+    }
+
+    #[test]
+    fn a_big_reward_is_worth_several_levels() {
+        let mut log = QuestLog::new("Eowyn");
+        log.accept(&Preset::LegendaryNPC.spawn("Tom Bombadil"), "dragon_slayer")
+            .unwrap();
+        let mut dragon = Preset::DragonBoss.spawn("Smaug");
+        dragon.set_health(0);
+        log.record_defeat(&dragon);
+
+        let mut eowyn = PlayerWarrior::new("Eowyn");
+        log.claim("dragon_slayer", &mut eowyn).unwrap();
+        // 100 + 200 + 300 + 400 experience takes level 1 to level 5
+        assert_eq!(eowyn.get_level(), 5);
+        assert_eq!(eowyn.experience, 0);
+    }
+}
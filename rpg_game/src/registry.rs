@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::actor::{ActorTemplate, Preset};
+use crate::character::Character;
+use crate::players::{PlayerMage, PlayerWarrior};
+use crate::templates::TemplateLibrary;
+
+pub type Constructor = Box<dyn Fn(&str) -> Box<dyn Character>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegistryError {
+    UnknownId(String),
+    DuplicateId(String),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegistryError::UnknownId(id) => write!(f, "no character registered as `{}`", id),
+            RegistryError::DuplicateId(id) => write!(f, "`{}` is already registered", id),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+// Maps string IDs like "goblin_warrior" to constructors taking the character's name
+#[derive(Default)]
+pub struct CharacterRegistry {
+    constructors: BTreeMap<String, Constructor>,
+}
+
+impl CharacterRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Both player classes plus every preset
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.insert("player_warrior", |name| Box::new(PlayerWarrior::new(name)));
+        registry.insert("player_mage", |name| Box::new(PlayerMage::new(name)));
+        for preset in Preset::ALL {
+            registry.insert(&preset.template().id, move |name| {
+                Box::new(preset.spawn(name))
+            });
+        }
+        registry
+    }
+
+    pub fn register<F>(&mut self, id: &str, constructor: F) -> Result<(), RegistryError>
+    where
+        F: Fn(&str) -> Box<dyn Character> + 'static,
+    {
+        if self.contains(id) {
+            return Err(RegistryError::DuplicateId(id.to_string()));
+        }
+        self.insert(id, constructor);
+        Ok(())
+    }
+
+    fn insert<F>(&mut self, id: &str, constructor: F)
+    where
+        F: Fn(&str) -> Box<dyn Character> + 'static,
+    {
+        self.constructors
+            .insert(id.to_string(), Box::new(constructor));
+    }
+
+    pub fn register_template(&mut self, template: ActorTemplate) -> Result<(), RegistryError> {
+        let id = template.id.clone();
+        self.register(&id, move |name| Box::new(template.spawn_named(name)))
+    }
+
+    // Registers every template in the library, stopping at the first ID already taken
+    pub fn register_library(&mut self, library: &TemplateLibrary) -> Result<(), RegistryError> {
+        library
+            .iter()
+            .try_for_each(|template| self.register_template(template.clone()))
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.constructors.contains_key(id)
+    }
+
+    // Alphabetical, so listings are stable
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.constructors.keys().map(String::as_str)
+    }
+
+    pub fn spawn(&self, id: &str, name: &str) -> Result<Box<dyn Character>, RegistryError> {
+        self.constructors
+            .get(id)
+            .map(|constructor| constructor(name))
+            .ok_or_else(|| RegistryError::UnknownId(id.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtins_spawn_by_id() {
+        let registry = CharacterRegistry::with_builtins();
+        let warrior = registry.spawn("player_warrior", "Aragorn").unwrap();
+        assert_eq!(warrior.get_name(), "Aragorn");
+        let dragon = registry.spawn("dragon_boss", "Smaug").unwrap();
+        assert_eq!(dragon.get_name(), "Smaug");
+        assert!(dragon.is_invincible());
+        assert!(registry.ids().any(|id| id == "goblin_mage"));
+    }
+
+    #[test]
+    fn unknown_ids_are_an_error() {
+        let registry = CharacterRegistry::with_builtins();
+        assert_eq!(
+            registry.spawn("balrog", "Durin's Bane").err(),
+            Some(RegistryError::UnknownId("balrog".to_string()))
+        );
+    }
+
+    #[test]
+    fn ids_can_only_be_registered_once() {
+        let mut registry = CharacterRegistry::with_builtins();
+        let taken = registry.register("player_mage", |name| Box::new(PlayerWarrior::new(name)));
+        assert_eq!(
+            taken,
+            Err(RegistryError::DuplicateId("player_mage".to_string()))
+        );
+
+        registry
+            .register("ranger", |name| Box::new(PlayerWarrior::new(name)))
+            .unwrap();
+        assert_eq!(
+            registry.spawn("ranger", "Halbarad").unwrap().get_name(),
+            "Halbarad"
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use std::fmt;
+
+use crate::character::{BattleResult, TurnSummary};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EndReason {
+    Knockout,
+    RoundLimit,
+    Fled,
+}
+
+// Damage dealt counts direct hits only; damage taken also includes status ticks
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FighterReport {
+    pub name: String,
+    pub damage_dealt: u32,
+    pub damage_taken: u32,
+    pub healing_done: u32,
+    pub overkill: u32, // Damage past the point the opponent was already down
+    pub missed: u32,
+    pub blocked: u32, // Hits that landed but were fully absorbed or resisted
+    pub items_used: u32,
+    pub remaining_health: u32,
+}
+
+impl FighterReport {
+    pub fn new(name: &str) -> Self {
+        FighterReport {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn record_turn(&mut self, turn: &TurnSummary) {
+        self.damage_dealt += turn.damage_dealt;
+        self.healing_done += turn.healing_done;
+        self.overkill += turn.overkill;
+        self.missed += turn.missed;
+        self.blocked += turn.blocked;
+        self.items_used += turn.items_used;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BattleReport {
+    pub result: BattleResult,
+    pub end_reason: EndReason,
+    pub rounds: u32,
+    pub fighters: [FighterReport; 2],
+}
+
+impl BattleReport {
+    pub fn winner(&self) -> Option<&FighterReport> {
+        match self.result {
+            BattleResult::Winner1 => Some(&self.fighters[0]),
+            BattleResult::Winner2 => Some(&self.fighters[1]),
+            BattleResult::Draw => None,
+        }
+    }
+}
+
+impl fmt::Display for BattleReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:?} after {} rounds ({:?})",
+            self.result, self.rounds, self.end_reason
+        )?;
+        for fighter in &self.fighters {
+            writeln!(
+                f,
+                "  {}: dealt {}, took {}, healed {}, overkill {}, {} missed, {} blocked, {} items used, {} HP left",
+                fighter.name,
+                fighter.damage_dealt,
+                fighter.damage_taken,
+                fighter.healing_done,
+                fighter.overkill,
+                fighter.missed,
+                fighter.blocked,
+                fighter.items_used,
+                fighter.remaining_health
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::{Character, CombatSystem};
+    use crate::config::{BattleConfig, FirstStrike};
+    use crate::enemies::{DragonBoss, GoblinWarrior};
+    use crate::players::PlayerWarrior;
+    use crate::status::{StatusEffect, StatusKind};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn knockouts_record_damage_and_overkill() {
+        let config = BattleConfig {
+            first_strike: FirstStrike::Challenger,
+            ..BattleConfig::default()
+        };
+        let mut combat = CombatSystem::new().without_rng().with_config(config);
+        let mut hero = PlayerWarrior::new("Boromir");
+        let mut goblin = GoblinWarrior::new("Grishnakh");
+        goblin.set_health(1);
+
+        let report = combat.battle_with_report(&mut hero, &mut goblin);
+        assert_eq!(report.result, BattleResult::Winner1);
+        assert_eq!(report.end_reason, EndReason::Knockout);
+        assert_eq!(report.rounds, 1);
+        assert_eq!(report.winner().map(|w| w.name.as_str()), Some("Boromir"));
+
+        let [hero_report, goblin_report] = &report.fighters;
+        assert_eq!(hero_report.damage_dealt, 1);
+        assert!(hero_report.overkill > 0);
+        assert_eq!(goblin_report.damage_taken, 1);
+        assert_eq!(goblin_report.remaining_health, 0);
+    }
+
+    #[test]
+    fn misses_are_counted() {
+        let config = BattleConfig {
+            max_rounds: 5,
+            ..BattleConfig::default()
+        };
+        let mut combat = CombatSystem::new()
+            .with_rng(StdRng::seed_from_u64(11))
+            .with_config(config);
+        let mut hero = PlayerWarrior::new("Boromir");
+        let mut dragon = DragonBoss::new("Smaug");
+        hero.apply_status(StatusEffect::new(StatusKind::Slow, 90, 10));
+
+        let report = combat.battle_with_report(&mut hero, &mut dragon);
+        assert!(report.fighters[0].missed > 0);
+    }
+
+    #[test]
+    fn fights_still_going_at_the_round_limit_say_so() {
+        let config = BattleConfig {
+            max_rounds: 1,
+            ..BattleConfig::default()
+        };
+        let mut combat = CombatSystem::new().without_rng().with_config(config);
+        let mut hero = PlayerWarrior::new("Boromir");
+        let mut dragon = DragonBoss::new("Smaug");
+
+        let report = combat.battle_with_report(&mut hero, &mut dragon);
+        assert_eq!(report.end_reason, EndReason::RoundLimit);
+        assert_eq!(report.result, BattleResult::Draw);
+        assert!(report.winner().is_none());
+        assert_eq!(report.fighters[0].remaining_health, hero.get_health());
+    }
+}
@@ -0,0 +1,341 @@
+use crate::abilities::Abilities;
+use crate::actor::Actor;
+use crate::character::{Character, CharacterType};
+use crate::classes::{AttackType, ClassType, DamageType};
+use crate::damage::AttackProfile;
+use crate::elements::Affinities;
+use crate::equipment::Equipment;
+use crate::items::Inventory;
+use crate::players::{Player, PlayerMage, PlayerWarrior};
+use crate::save::Persist;
+use crate::status::{StatusEffect, StatusEffects};
+
+// Any kind of character, so parties, rosters and tournament brackets can mix them
+#[derive(Debug, Clone)]
+pub enum Combatant {
+    Warrior(PlayerWarrior),
+    Mage(PlayerMage),
+    Actor(Box<Actor>), // Boxed, it carries its whole template
+}
+
+impl Combatant {
+    pub fn as_character(&self) -> &dyn Character {
+        match self {
+            Combatant::Warrior(warrior) => warrior,
+            Combatant::Mage(mage) => mage,
+            Combatant::Actor(actor) => actor.as_ref(),
+        }
+    }
+
+    pub fn as_character_mut(&mut self) -> &mut dyn Character {
+        match self {
+            Combatant::Warrior(warrior) => warrior,
+            Combatant::Mage(mage) => mage,
+            Combatant::Actor(actor) => actor.as_mut(),
+        }
+    }
+
+    pub fn as_player(&self) -> &dyn Player {
+        match self {
+            Combatant::Warrior(warrior) => warrior,
+            Combatant::Mage(mage) => mage,
+            Combatant::Actor(actor) => actor.as_ref(),
+        }
+    }
+
+    pub fn as_player_mut(&mut self) -> &mut dyn Player {
+        match self {
+            Combatant::Warrior(warrior) => warrior,
+            Combatant::Mage(mage) => mage,
+            Combatant::Actor(actor) => actor.as_mut(),
+        }
+    }
+}
+
+impl From<PlayerWarrior> for Combatant {
+    fn from(warrior: PlayerWarrior) -> Self {
+        Combatant::Warrior(warrior)
+    }
+}
+
+impl From<PlayerMage> for Combatant {
+    fn from(mage: PlayerMage) -> Self {
+        Combatant::Mage(mage)
+    }
+}
+
+impl From<Actor> for Combatant {
+    fn from(actor: Actor) -> Self {
+        Combatant::Actor(Box::new(actor))
+    }
+}
+
+// Every method forwards, including the defaulted ones, so overrides in the wrapped
+// character are never bypassed
+impl Character for Combatant {
+    fn new(name: &str) -> Self {
+        Combatant::Warrior(PlayerWarrior::new(name))
+    }
+
+    fn revive(&mut self) {
+        self.as_character_mut().revive()
+    }
+
+    fn get_name(&self) -> &str {
+        self.as_character().get_name()
+    }
+
+    fn get_health(&self) -> u32 {
+        self.as_character().get_health()
+    }
+
+    fn set_health(&mut self, health: u32) {
+        self.as_character_mut().set_health(health)
+    }
+
+    fn is_invincible(&self) -> bool {
+        self.as_character().is_invincible()
+    }
+
+    fn get_class_type(&self) -> &ClassType {
+        self.as_character().get_class_type()
+    }
+
+    fn get_character_type(&self) -> &CharacterType {
+        self.as_character().get_character_type()
+    }
+
+    fn get_status_effects(&self) -> &StatusEffects {
+        self.as_character().get_status_effects()
+    }
+
+    fn get_status_effects_mut(&mut self) -> &mut StatusEffects {
+        self.as_character_mut().get_status_effects_mut()
+    }
+
+    fn apply_status(&mut self, effect: StatusEffect) {
+        self.as_character_mut().apply_status(effect)
+    }
+
+    fn is_stunned(&self) -> bool {
+        self.as_character().is_stunned()
+    }
+
+    fn get_abilities(&self) -> &Abilities {
+        self.as_character().get_abilities()
+    }
+
+    fn get_abilities_mut(&mut self) -> &mut Abilities {
+        self.as_character_mut().get_abilities_mut()
+    }
+
+    fn get_inventory(&self) -> Option<&Inventory> {
+        self.as_character().get_inventory()
+    }
+
+    fn get_inventory_mut(&mut self) -> Option<&mut Inventory> {
+        self.as_character_mut().get_inventory_mut()
+    }
+
+    fn get_equipment(&self) -> Option<&Equipment> {
+        self.as_character().get_equipment()
+    }
+
+    fn get_base_health(&self) -> u32 {
+        self.as_character().get_base_health()
+    }
+
+    fn get_calculated_max_health(&self) -> u32 {
+        self.as_character().get_calculated_max_health()
+    }
+
+    fn get_damage_type(&self) -> DamageType {
+        self.as_character().get_damage_type()
+    }
+
+    fn get_attack_type(&self) -> AttackType {
+        self.as_character().get_attack_type()
+    }
+
+    fn get_base_damage(&self) -> u32 {
+        self.as_character().get_base_damage()
+    }
+
+    fn get_calculated_damage(&self) -> u32 {
+        self.as_character().get_calculated_damage()
+    }
+
+    fn get_mana(&self) -> u32 {
+        self.as_character().get_mana()
+    }
+
+    fn get_max_mana(&self) -> u32 {
+        self.as_character().get_max_mana()
+    }
+
+    fn set_mana(&mut self, mana: u32) {
+        self.as_character_mut().set_mana(mana)
+    }
+
+    fn get_gold(&self) -> u32 {
+        self.as_character().get_gold()
+    }
+
+    fn set_gold(&mut self, gold: u32) {
+        self.as_character_mut().set_gold(gold)
+    }
+
+    fn get_mana_regen(&self) -> u32 {
+        self.as_character().get_mana_regen()
+    }
+
+    fn restore_mana(&mut self, amount: u32) {
+        self.as_character_mut().restore_mana(amount)
+    }
+
+    fn get_attack(&self) -> AttackProfile {
+        self.as_character().get_attack()
+    }
+
+    fn get_fallback_attack(&self) -> AttackProfile {
+        self.as_character().get_fallback_attack()
+    }
+
+    fn choose_attack(&self) -> AttackProfile {
+        self.as_character().choose_attack()
+    }
+
+    fn get_accuracy(&self) -> f32 {
+        self.as_character().get_accuracy()
+    }
+
+    fn get_evasion(&self) -> f32 {
+        self.as_character().get_evasion()
+    }
+
+    fn get_crit_chance(&self) -> f32 {
+        self.as_character().get_crit_chance()
+    }
+
+    fn get_crit_multiplier(&self) -> f32 {
+        self.as_character().get_crit_multiplier()
+    }
+
+    fn get_armor(&self) -> u32 {
+        self.as_character().get_armor()
+    }
+
+    fn get_magic_resistance(&self) -> u32 {
+        self.as_character().get_magic_resistance()
+    }
+
+    fn get_defense(&self, damage_type: DamageType) -> u32 {
+        self.as_character().get_defense(damage_type)
+    }
+
+    fn get_affinities(&self) -> Affinities {
+        self.as_character().get_affinities()
+    }
+
+    fn get_speed(&self) -> u32 {
+        self.as_character().get_speed()
+    }
+
+    fn get_effective_speed(&self) -> u32 {
+        self.as_character().get_effective_speed()
+    }
+
+    fn can_attack(&self) -> bool {
+        self.as_character().can_attack()
+    }
+
+    fn is_alive(&self) -> bool {
+        self.as_character().is_alive()
+    }
+
+    fn get_flee_threshold(&self) -> f32 {
+        self.as_character().get_flee_threshold()
+    }
+
+    fn wants_to_flee(&self) -> bool {
+        self.as_character().wants_to_flee()
+    }
+}
+
+impl Player for Combatant {
+    fn level_up(&mut self) {
+        self.as_player_mut().level_up()
+    }
+
+    fn get_experience(&self) -> u32 {
+        self.as_player().get_experience()
+    }
+
+    fn get_level(&self) -> u32 {
+        self.as_player().get_level()
+    }
+
+    fn add_experience(&mut self, exp: u32) {
+        self.as_player_mut().add_experience(exp)
+    }
+
+    fn as_persist(&self) -> Option<&dyn Persist> {
+        self.as_player().as_persist()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::CombatSystem;
+    use crate::combat::Arena;
+    use crate::enemies::{GoblinMage, GoblinWarrior};
+    use crate::party::{PartyOutcome, PartyRules, Side};
+
+    fn mixed_party() -> Vec<Combatant> {
+        vec![
+            PlayerWarrior::new("Aragorn").into(),
+            PlayerMage::new("Gandalf").into(),
+            GoblinWarrior::new("Turncoat").into(),
+        ]
+    }
+
+    #[test]
+    fn mixed_parties_fight_and_keep_their_wounds() {
+        let mut party = mixed_party();
+        let mut goblins = vec![GoblinWarrior::new("Azog"), GoblinMage::new("Gorbag")];
+
+        let result = CombatSystem::new().without_rng().party_battle(
+            &mut party,
+            &mut goblins,
+            &PartyRules::default(),
+        );
+        assert_eq!(result.outcome, PartyOutcome::Won(Side::Players));
+        let fresh = mixed_party();
+        let total = |members: &[Combatant]| members.iter().map(|m| m.get_health()).sum::<u32>();
+        assert!(total(&party) < total(&fresh));
+        assert_eq!(party[1].get_name(), "Gandalf");
+        assert!(matches!(party[1], Combatant::Mage(_)));
+    }
+
+    #[test]
+    fn tournaments_bracket_mixed_combatants() {
+        let participants = mixed_party();
+        let mut arena = Arena::with_combat(CombatSystem::new().without_rng());
+
+        let result = arena.tournament(participants.clone()).unwrap();
+        assert_eq!(result.matches.len(), 2); // One bye in the first round
+        assert!(
+            participants
+                .iter()
+                .any(|p| p.get_name() == result.champion.get_name())
+        );
+        assert!(result.champion.is_alive());
+        // The bracket fights copies, so the roster itself is untouched
+        assert!(
+            participants
+                .iter()
+                .all(|p| p.get_health() == p.get_calculated_max_health())
+        );
+    }
+}
@@ -0,0 +1,828 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::character::{Character, CharacterType, NPCImportance};
+use crate::classes::{ClassType, Mage, Warrior};
+use crate::equipment::{EquipSlot, Equipment};
+use crate::items::{Inventory, item_by_id};
+use crate::players::{Player, PlayerMage, PlayerWarrior};
+
+pub const SAVE_HEADER: &str = "RPGSAVE";
+pub const SAVE_VERSION: u32 = 5;
+pub const OLDEST_SAVE_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    EmptySlot(u32),
+    NotASave, // Missing or unrecognised header line
+    UnsupportedVersion { found: u32, supported: u32 },
+    Malformed { line: usize, content: String },
+    DuplicateField { line: usize, field: String },
+    MissingField(String),
+    InvalidValue { field: String, value: String },
+    UnknownKind(String),
+    NotPersistable(String), // A player kind with no save format, such as an actor
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(error) => write!(f, "could not access save file: {}", error),
+            SaveError::EmptySlot(slot) => write!(f, "save slot {} is empty", slot),
+            SaveError::NotASave => write!(f, "not a save file (missing {} header)", SAVE_HEADER),
+            SaveError::UnsupportedVersion { found, supported } if found < supported => write!(
+                f,
+                "save version {} is outdated, this game reads version {}",
+                found, supported
+            ),
+            SaveError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "save version {} is newer than this game supports ({})",
+                found, supported
+            ),
+            SaveError::Malformed { line, content } => {
+                write!(f, "line {} is corrupt: {:?}", line, content)
+            }
+            SaveError::DuplicateField { line, field } => {
+                write!(f, "line {} sets field `{}` a second time", line, field)
+            }
+            SaveError::MissingField(field) => write!(f, "save is missing field `{}`", field),
+            SaveError::InvalidValue { field, value } => {
+                write!(f, "field `{}` has invalid value {:?}", field, value)
+            }
+            SaveError::UnknownKind(kind) => write!(f, "unknown character kind {:?}", kind),
+            SaveError::NotPersistable(name) => write!(f, "{} can't be saved", name),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SaveError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(error: io::Error) -> Self {
+        SaveError::Io(error)
+    }
+}
+
+// Ordered key=value pairs under a versioned header line
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SaveRecord {
+    fields: Vec<(String, String)>,
+}
+
+impl SaveRecord {
+    pub fn set(&mut self, key: &str, value: impl ToString) {
+        let value = value.to_string();
+        match self.fields.iter_mut().find(|(k, _)| k == key) {
+            Some(field) => field.1 = value,
+            None => self.fields.push((key.to_string(), value)),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Result<&str, SaveError> {
+        self.fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+            .ok_or_else(|| SaveError::MissingField(key.to_string()))
+    }
+
+    pub fn parse<T: FromStr>(&self, key: &str) -> Result<T, SaveError> {
+        let value = self.get(key)?;
+        value.parse().map_err(|_| SaveError::InvalidValue {
+            field: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{} {}\n", SAVE_HEADER, SAVE_VERSION);
+        for (key, value) in &self.fields {
+            text.push_str(&format!("{}={}\n", key, escape(value)));
+        }
+        text
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, SaveError> {
+        let mut lines = text.lines().enumerate();
+        let version = lines
+            .next()
+            .and_then(|(_, header)| header.strip_prefix(SAVE_HEADER))
+            .ok_or(SaveError::NotASave)?;
+        let version: u32 = version.trim().parse().map_err(|_| SaveError::NotASave)?;
+        if !(OLDEST_SAVE_VERSION..=SAVE_VERSION).contains(&version) {
+            return Err(SaveError::UnsupportedVersion {
+                found: version,
+                supported: SAVE_VERSION,
+            });
+        }
+
+        let mut record = SaveRecord::default();
+        for (index, line) in lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .filter(|(key, _)| !key.is_empty())
+                .ok_or_else(|| SaveError::Malformed {
+                    line: index + 1,
+                    content: line.to_string(),
+                })?;
+            if record.get(key).is_ok() {
+                return Err(SaveError::DuplicateField {
+                    line: index + 1,
+                    field: key.to_string(),
+                });
+            }
+            record.set(key, unescape(value));
+        }
+        migrate(record, version)
+    }
+}
+
+pub struct Migration {
+    pub from: u32, // Upgrades saves of this version to the next one
+    pub description: &'static str,
+    pub apply: fn(&mut SaveRecord) -> Result<(), SaveError>,
+}
+
+// One step per format change, in order. Bump SAVE_VERSION and add a step here whenever
+// the fields a save carries change.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 1,
+        description: "split compact class stats into one field each",
+        apply: split_class_stats,
+    },
+    Migration {
+        from: 2,
+        description: "start with an empty pack",
+        apply: add_empty_pack,
+    },
+    Migration {
+        from: 3,
+        description: "start with nothing equipped",
+        apply: add_empty_equipment,
+    },
+    Migration {
+        from: 4,
+        description: "start with no gold",
+        apply: add_empty_purse,
+    },
+];
+
+pub fn migrate(mut record: SaveRecord, version: u32) -> Result<SaveRecord, SaveError> {
+    for step in MIGRATIONS.iter().filter(|step| step.from >= version) {
+        (step.apply)(&mut record)?;
+    }
+    Ok(record)
+}
+
+// v1 packed class stats into one value, e.g. "warrior:10" or "mage:100:10"
+fn split_class_stats(record: &mut SaveRecord) -> Result<(), SaveError> {
+    let class = record.get("class")?.to_string();
+    let parts: Vec<&str> = class.split(':').collect();
+    match parts.as_slice() {
+        ["warrior", strength] => {
+            record.set("class", "warrior");
+            record.set("class.strength", strength);
+        }
+        ["mage", mana, power] => {
+            record.set("class", "mage");
+            record.set("class.mana", mana);
+            record.set("class.power", power);
+        }
+        _ => {
+            return Err(SaveError::InvalidValue {
+                field: "class".to_string(),
+                value: class,
+            });
+        }
+    }
+    Ok(())
+}
+
+// v2 didn't save anything a player carries
+fn add_empty_pack(record: &mut SaveRecord) -> Result<(), SaveError> {
+    record.set("inventory", "");
+    Ok(())
+}
+
+// v3 didn't save worn gear
+fn add_empty_equipment(record: &mut SaveRecord) -> Result<(), SaveError> {
+    record.set("equipment", "");
+    Ok(())
+}
+
+// v4 didn't save gold
+fn add_empty_purse(record: &mut SaveRecord) -> Result<(), SaveError> {
+    record.set("gold", 0);
+    Ok(())
+}
+
+// Keeps every value on a single line
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                result.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                result.push('\\');
+                chars.next();
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+pub fn write_class(record: &mut SaveRecord, class: &ClassType) {
+    match class {
+        ClassType::Warrior(warrior) => {
+            record.set("class", "warrior");
+            record.set("class.strength", warrior.get_strength());
+        }
+        ClassType::Mage(mage) => {
+            record.set("class", "mage");
+            record.set("class.mana", mage.get_mana());
+            record.set("class.power", mage.get_power());
+        }
+    }
+}
+
+pub fn read_class(record: &SaveRecord) -> Result<ClassType, SaveError> {
+    match record.get("class")? {
+        "warrior" => Ok(ClassType::Warrior(Warrior::with_strength(
+            record.parse("class.strength")?,
+        ))),
+        "mage" => Ok(ClassType::Mage(Mage::with_stats(
+            record.parse("class.mana")?,
+            record.parse("class.power")?,
+        ))),
+        other => Err(SaveError::InvalidValue {
+            field: "class".to_string(),
+            value: other.to_string(),
+        }),
+    }
+}
+
+// Stacks as `id:count`, comma separated, e.g. "health_potion:3,chainmail:1"
+pub fn write_inventory(record: &mut SaveRecord, inventory: &Inventory) {
+    let stacks: Vec<String> = inventory
+        .iter()
+        .map(|stack| format!("{}:{}", stack.item.id, stack.quantity))
+        .collect();
+    record.set("inventory", stacks.join(","));
+}
+
+// Packed into a default inventory, so a save can't carry more than play allows
+pub fn read_inventory(record: &SaveRecord) -> Result<Inventory, SaveError> {
+    let value = record.get("inventory")?;
+    let invalid = || SaveError::InvalidValue {
+        field: "inventory".to_string(),
+        value: value.to_string(),
+    };
+    let mut inventory = Inventory::default();
+    for stack in value.split(',').filter(|stack| !stack.is_empty()) {
+        let (id, count) = stack.split_once(':').ok_or_else(invalid)?;
+        let count: u32 = count.parse().map_err(|_| invalid())?;
+        inventory.add_by_id(id, count).map_err(|_| invalid())?;
+    }
+    Ok(inventory)
+}
+
+// Item IDs of whatever is worn, comma separated
+pub fn write_equipment(record: &mut SaveRecord, equipment: &Equipment) {
+    let worn: Vec<&str> = [EquipSlot::Weapon, EquipSlot::Armor, EquipSlot::Accessory]
+        .into_iter()
+        .filter_map(|slot| equipment.get(slot))
+        .map(|item| item.id.as_str())
+        .collect();
+    record.set("equipment", worn.join(","));
+}
+
+// Each piece goes through the normal equip checks, so a save can't dress a mage in
+// plate or wear two weapons
+pub fn read_equipment(record: &SaveRecord, class: &ClassType) -> Result<Equipment, SaveError> {
+    let value = record.get("equipment")?;
+    let invalid = || SaveError::InvalidValue {
+        field: "equipment".to_string(),
+        value: value.to_string(),
+    };
+    let mut equipment = Equipment::default();
+    for id in value.split(',').filter(|id| !id.is_empty()) {
+        let mut worn = Inventory::default();
+        worn.add(item_by_id(id).ok_or_else(invalid)?, 1)
+            .map_err(|_| invalid())?;
+        equipment
+            .equip(id, class, &mut worn)
+            .map_err(|_| invalid())?;
+        if worn.iter().next().is_some() {
+            return Err(invalid()); // It replaced an earlier piece in the same slot
+        }
+    }
+    Ok(equipment)
+}
+
+pub fn encode_character_type(character_type: &CharacterType) -> String {
+    match character_type {
+        CharacterType::Player { level } => format!("player:{}", level),
+        CharacterType::Enemy { threat_level } => format!("enemy:{}", threat_level),
+        CharacterType::NPC { importance } => format!("npc:{:?}", importance),
+    }
+}
+
+pub fn decode_character_type(value: &str) -> Option<CharacterType> {
+    let (kind, detail) = value.split_once(':')?;
+    match kind {
+        "player" => Some(CharacterType::Player {
+            level: detail.parse().ok()?,
+        }),
+        "enemy" => Some(CharacterType::Enemy {
+            threat_level: detail.parse().ok()?,
+        }),
+        "npc" => {
+            let importance = match detail {
+                "Minor" => NPCImportance::Minor,
+                "Normal" => NPCImportance::Normal,
+                "Important" => NPCImportance::Important,
+                "Legendary" => NPCImportance::Legendary,
+                _ => return None,
+            };
+            Some(CharacterType::NPC { importance })
+        }
+        _ => None,
+    }
+}
+
+fn decode_field<T>(
+    record: &SaveRecord,
+    key: &str,
+    decode: impl Fn(&str) -> Option<T>,
+) -> Result<T, SaveError> {
+    let value = record.get(key)?;
+    decode(value).ok_or_else(|| SaveError::InvalidValue {
+        field: key.to_string(),
+        value: value.to_string(),
+    })
+}
+
+// Progress only: status effects and cooldowns belong to a single battle
+pub trait Persist: Player {
+    fn to_record(&self) -> SaveRecord;
+    fn from_record(record: &SaveRecord) -> Result<Self, SaveError>
+    where
+        Self: Sized;
+}
+
+fn write_progress(record: &mut SaveRecord, player: &dyn Player, kind: &str, max_health: u32) {
+    record.set("kind", kind);
+    record.set("name", player.get_name());
+    record.set("level", player.get_level());
+    record.set("experience", player.get_experience());
+    record.set("gold", player.get_gold());
+    record.set("health", player.get_health());
+    record.set("max_health", max_health);
+    write_class(record, player.get_class_type());
+    record.set(
+        "character_type",
+        encode_character_type(player.get_character_type()),
+    );
+    if let Some(inventory) = player.get_inventory() {
+        write_inventory(record, inventory);
+    }
+    if let Some(equipment) = player.get_equipment() {
+        write_equipment(record, equipment);
+    }
+}
+
+fn read_level(record: &SaveRecord) -> Result<u32, SaveError> {
+    match record.parse("level")? {
+        0 => Err(SaveError::InvalidValue {
+            field: "level".to_string(),
+            value: "0".to_string(),
+        }),
+        level => Ok(level),
+    }
+}
+
+// A player's character type always carries the same level as the save itself
+fn read_character_type(record: &SaveRecord, level: u32) -> Result<CharacterType, SaveError> {
+    let character_type = decode_field(record, "character_type", decode_character_type)?;
+    match character_type {
+        CharacterType::Player { level: saved } if saved == level => Ok(character_type),
+        _ => Err(SaveError::InvalidValue {
+            field: "character_type".to_string(),
+            value: record.get("character_type")?.to_string(),
+        }),
+    }
+}
+
+// Health can never exceed its maximum in a save this game wrote
+fn read_health(record: &SaveRecord) -> Result<(u32, u32), SaveError> {
+    let health: u32 = record.parse("health")?;
+    let max_health: u32 = record.parse("max_health")?;
+    if health > max_health {
+        return Err(SaveError::InvalidValue {
+            field: "health".to_string(),
+            value: health.to_string(),
+        });
+    }
+    Ok((health, max_health))
+}
+
+impl Persist for PlayerWarrior {
+    fn to_record(&self) -> SaveRecord {
+        let mut record = SaveRecord::default();
+        write_progress(&mut record, self, "warrior", self.max_health);
+        record
+    }
+
+    fn from_record(record: &SaveRecord) -> Result<Self, SaveError> {
+        let mut warrior = PlayerWarrior::new(record.get("name")?);
+        (warrior.health, warrior.max_health) = read_health(record)?;
+        warrior.level = read_level(record)?;
+        warrior.experience = record.parse("experience")?;
+        warrior.gold = record.parse("gold")?;
+        warrior.class = read_class(record)?;
+        warrior.character_type = read_character_type(record, warrior.level)?;
+        warrior.inventory = read_inventory(record)?;
+        warrior.equipment = read_equipment(record, &warrior.class)?;
+        warrior.refresh_max_health();
+        Ok(warrior)
+    }
+}
+
+impl Persist for PlayerMage {
+    fn to_record(&self) -> SaveRecord {
+        let mut record = SaveRecord::default();
+        write_progress(&mut record, self, "mage", self.max_health);
+        record.set("mana", self.mana);
+        record.set("max_mana", self.max_mana);
+        record
+    }
+
+    fn from_record(record: &SaveRecord) -> Result<Self, SaveError> {
+        let mut mage = PlayerMage::new(record.get("name")?);
+        (mage.health, mage.max_health) = read_health(record)?;
+        mage.level = read_level(record)?;
+        mage.experience = record.parse("experience")?;
+        mage.gold = record.parse("gold")?;
+        mage.class = read_class(record)?;
+        mage.character_type = read_character_type(record, mage.level)?;
+        mage.inventory = read_inventory(record)?;
+        mage.equipment = read_equipment(record, &mage.class)?;
+        mage.refresh_max_health();
+        mage.max_mana = record.parse("max_mana")?;
+        if mage.max_mana != mage.calculate_max_mana() {
+            return Err(SaveError::InvalidValue {
+                field: "max_mana".to_string(),
+                value: mage.max_mana.to_string(),
+            });
+        }
+        mage.mana = record.parse::<u32>("mana")?.min(mage.max_mana);
+        Ok(mage)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SavedPlayer {
+    Warrior(PlayerWarrior),
+    Mage(PlayerMage),
+}
+
+impl SavedPlayer {
+    pub fn from_record(record: &SaveRecord) -> Result<Self, SaveError> {
+        match record.get("kind")? {
+            "warrior" => Ok(SavedPlayer::Warrior(PlayerWarrior::from_record(record)?)),
+            "mage" => Ok(SavedPlayer::Mage(PlayerMage::from_record(record)?)),
+            other => Err(SaveError::UnknownKind(other.to_string())),
+        }
+    }
+
+    pub fn to_record(&self) -> SaveRecord {
+        match self {
+            SavedPlayer::Warrior(warrior) => warrior.to_record(),
+            SavedPlayer::Mage(mage) => mage.to_record(),
+        }
+    }
+
+    pub fn as_player(&self) -> &dyn Player {
+        match self {
+            SavedPlayer::Warrior(warrior) => warrior,
+            SavedPlayer::Mage(mage) => mage,
+        }
+    }
+}
+
+pub type SlotEntry = (u32, Result<SavedPlayer, SaveError>);
+
+// One file per numbered slot inside a save directory
+#[derive(Debug, Clone)]
+pub struct SaveSlots {
+    dir: PathBuf,
+}
+
+impl SaveSlots {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        SaveSlots { dir: dir.into() }
+    }
+
+    pub fn path(&self, slot: u32) -> PathBuf {
+        self.dir.join(format!("slot_{}.sav", slot))
+    }
+
+    // Writes to a temporary file first so a crash never leaves a half-written save
+    pub fn save(&self, slot: u32, player: &dyn Persist) -> Result<(), SaveError> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(slot);
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, player.to_record().to_text())?;
+        fs::rename(&temp, &path)?;
+        Ok(())
+    }
+
+    pub fn load(&self, slot: u32) -> Result<SavedPlayer, SaveError> {
+        let text = match fs::read_to_string(self.path(slot)) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Err(SaveError::EmptySlot(slot));
+            }
+            Err(error) => return Err(error.into()),
+        };
+        SavedPlayer::from_record(&SaveRecord::from_text(&text)?)
+    }
+
+    // Every occupied slot in order, including ones that fail to load
+    pub fn list(&self) -> Result<Vec<SlotEntry>, SaveError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error.into()),
+        };
+
+        let mut slots = Vec::new();
+        for entry in entries {
+            let file_name = entry?.file_name();
+            let slot = file_name
+                .to_str()
+                .and_then(|name| name.strip_prefix("slot_"))
+                .and_then(|name| name.strip_suffix(".sav"))
+                .and_then(|number| number.parse::<u32>().ok());
+            if let Some(slot) = slot {
+                slots.push(slot);
+            }
+        }
+        slots.sort();
+        Ok(slots
+            .into_iter()
+            .map(|slot| (slot, self.load(slot)))
+            .collect())
+    }
+
+    pub fn delete(&self, slot: u32) -> Result<(), SaveError> {
+        match fs::remove_file(self.path(slot)) {
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                Err(SaveError::EmptySlot(slot))
+            }
+            result => Ok(result?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::apply_item;
+
+    const V1_WARRIOR: &str = include_str!("../fixtures/saves/v1_warrior.sav");
+    const V1_MAGE: &str = include_str!("../fixtures/saves/v1_mage.sav");
+
+    fn warrior_record() -> SaveRecord {
+        let mut warrior = PlayerWarrior::new("Frodo");
+        warrior.add_experience(150);
+        warrior.inventory.add_by_id("health_potion", 3).unwrap();
+        warrior.inventory.add_by_id("ancient_key", 1).unwrap();
+        warrior.earn_gold(240);
+        warrior.to_record()
+    }
+
+    #[test]
+    fn round_trips_a_player() {
+        let record = warrior_record();
+        let loaded = SavedPlayer::from_record(&record).unwrap();
+        assert_eq!(loaded.as_player().get_level(), 2);
+        assert_eq!(loaded.to_record(), record);
+    }
+
+    #[test]
+    fn round_trips_the_inventory() {
+        let SavedPlayer::Warrior(warrior) = SavedPlayer::from_record(&warrior_record()).unwrap()
+        else {
+            panic!("the warrior loaded as a mage");
+        };
+        assert_eq!(warrior.inventory.count("health_potion"), 3);
+        assert_eq!(warrior.inventory.count("ancient_key"), 1);
+        assert_eq!(warrior.gold, 240);
+    }
+
+    #[test]
+    fn rejects_an_inventory_play_couldnt_hold() {
+        let mut record = warrior_record();
+        record.set("inventory", "health_potion:999");
+        assert!(PlayerWarrior::from_record(&record).is_err());
+        record.set("inventory", "moon_rock:1");
+        assert!(PlayerWarrior::from_record(&record).is_err());
+    }
+
+    #[test]
+    fn v2_saves_start_with_nothing_carried() {
+        let mut text = warrior_record().to_text();
+        text = text.replacen(&format!("RPGSAVE {}", SAVE_VERSION), "RPGSAVE 2", 1);
+        let text: String = text
+            .lines()
+            .filter(|line| !line.starts_with("inventory=") && !line.starts_with("gold="))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let record = SaveRecord::from_text(&text).unwrap();
+        let warrior = PlayerWarrior::from_record(&record).unwrap();
+        assert_eq!(warrior.inventory.iter().count(), 0);
+        assert_eq!(warrior.gold, 0);
+    }
+
+    fn armoured_record() -> SaveRecord {
+        let mut warrior = PlayerWarrior::new("Boromir");
+        warrior.inventory.add_by_id("chainmail", 1).unwrap();
+        warrior.equip("chainmail").unwrap();
+        warrior.health = warrior.max_health;
+        warrior.to_record()
+    }
+
+    #[test]
+    fn round_trips_equipment() {
+        let warrior = PlayerWarrior::from_record(&armoured_record()).unwrap();
+        let armor = warrior.equipment.get(EquipSlot::Armor).unwrap();
+        assert_eq!(armor.id, "chainmail");
+        assert_eq!(warrior.max_health, warrior.get_calculated_max_health());
+        assert_eq!(warrior.health, warrior.max_health);
+    }
+
+    #[test]
+    fn health_is_capped_when_saved_gear_is_missing() {
+        let mut record = armoured_record();
+        record.set("equipment", "");
+        let mut warrior = PlayerWarrior::from_record(&record).unwrap();
+        assert_eq!(warrior.max_health, warrior.get_calculated_max_health());
+        assert_eq!(warrior.health, warrior.max_health);
+
+        // Even an over-full character reports no healing rather than underflowing
+        warrior.health = warrior.max_health + 30;
+        let healed = apply_item(&item_by_id("health_potion").unwrap(), &mut warrior);
+        assert_eq!(healed.healed, 0);
+    }
+
+    #[test]
+    fn rejects_gear_the_class_cant_wear() {
+        let mut record = armoured_record();
+        record.set("equipment", "oak_staff");
+        assert!(PlayerWarrior::from_record(&record).is_err());
+        record.set("equipment", "flamebrand,longbow");
+        assert!(PlayerWarrior::from_record(&record).is_err());
+    }
+
+    #[test]
+    fn rejects_a_level_the_character_type_disagrees_with() {
+        let mut record = warrior_record();
+        record.set("character_type", "player:7");
+        assert!(matches!(
+            PlayerWarrior::from_record(&record),
+            Err(SaveError::InvalidValue { field, .. }) if field == "character_type"
+        ));
+
+        record.set("character_type", "enemy:2");
+        assert!(PlayerWarrior::from_record(&record).is_err());
+    }
+
+    #[test]
+    fn migrates_the_v1_warrior_fixture() {
+        let record = SaveRecord::from_text(V1_WARRIOR).unwrap();
+        assert_eq!(record.get("class").unwrap(), "warrior");
+        assert_eq!(record.get("class.strength").unwrap(), "10");
+
+        let SavedPlayer::Warrior(warrior) = SavedPlayer::from_record(&record).unwrap() else {
+            panic!("the warrior fixture loaded as a mage");
+        };
+        assert_eq!(warrior.get_name(), "Frodo");
+        assert_eq!(warrior.get_level(), 2);
+        assert_eq!(warrior.get_experience(), 150);
+        assert_eq!((warrior.health, warrior.max_health), (110, 110));
+        assert_eq!(warrior.get_class_type().get_strength(), Some(10));
+        assert_eq!(warrior.inventory.iter().count(), 0);
+    }
+
+    #[test]
+    fn migrates_the_v1_mage_fixture() {
+        let record = SaveRecord::from_text(V1_MAGE).unwrap();
+        assert_eq!(record.get("class").unwrap(), "mage");
+        assert_eq!(record.get("class.mana").unwrap(), "100");
+        assert_eq!(record.get("class.power").unwrap(), "10");
+
+        let SavedPlayer::Mage(mage) = SavedPlayer::from_record(&record).unwrap() else {
+            panic!("the mage fixture loaded as a warrior");
+        };
+        assert_eq!(mage.get_name(), "Gandalf");
+        assert_eq!(mage.get_level(), 2);
+        assert_eq!((mage.health, mage.max_health), (60, 82));
+        assert_eq!((mage.mana, mage.max_mana), (45, 110));
+        assert_eq!(mage.gold, 0);
+    }
+
+    #[test]
+    fn every_migration_step_runs_in_order() {
+        for (index, step) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(step.from, OLDEST_SAVE_VERSION + index as u32);
+        }
+        assert_eq!(OLDEST_SAVE_VERSION + MIGRATIONS.len() as u32, SAVE_VERSION);
+    }
+
+    #[test]
+    fn rejects_versions_newer_than_the_game() {
+        let text = V1_WARRIOR.replacen("RPGSAVE 1", &format!("RPGSAVE {}", SAVE_VERSION + 1), 1);
+        assert!(matches!(
+            SaveRecord::from_text(&text),
+            Err(SaveError::UnsupportedVersion { found, supported })
+                if found == SAVE_VERSION + 1 && supported == SAVE_VERSION
+        ));
+    }
+
+    #[test]
+    fn rejects_a_field_set_twice() {
+        let text = format!("{}gold=9999\n", warrior_record().to_text());
+        assert!(matches!(
+            SaveRecord::from_text(&text),
+            Err(SaveError::DuplicateField { field, .. }) if field == "gold"
+        ));
+    }
+
+    #[test]
+    fn v3_saves_start_with_nothing_equipped() {
+        let text = armoured_record()
+            .to_text()
+            .replacen(&format!("RPGSAVE {}", SAVE_VERSION), "RPGSAVE 3", 1)
+            .lines()
+            .filter(|line| !line.starts_with("equipment="))
+            .map(|line| format!("{}\n", line))
+            .collect::<String>();
+        let warrior = PlayerWarrior::from_record(&SaveRecord::from_text(&text).unwrap()).unwrap();
+        assert!(warrior.equipment.get(EquipSlot::Armor).is_none());
+        assert_eq!(warrior.inventory.iter().count(), 0); // The chainmail was being worn
+    }
+
+    #[test]
+    fn rejects_a_max_mana_the_level_disagrees_with() {
+        let mut mage = PlayerMage::new("Gandalf");
+        mage.add_experience(100);
+        let mut record = mage.to_record();
+        assert!(PlayerMage::from_record(&record).is_ok());
+
+        record.set("max_mana", 9999);
+        assert!(matches!(
+            PlayerMage::from_record(&record),
+            Err(SaveError::InvalidValue { field, .. }) if field == "max_mana"
+        ));
+    }
+
+    #[test]
+    fn v4_saves_start_with_no_gold() {
+        let text = warrior_record()
+            .to_text()
+            .replacen(&format!("RPGSAVE {}", SAVE_VERSION), "RPGSAVE 4", 1)
+            .lines()
+            .filter(|line| !line.starts_with("gold="))
+            .map(|line| format!("{}\n", line))
+            .collect::<String>();
+        let warrior = PlayerWarrior::from_record(&SaveRecord::from_text(&text).unwrap()).unwrap();
+        assert_eq!(warrior.gold, 0);
+        assert_eq!(warrior.inventory.count("health_potion"), 3);
+    }
+}
@@ -0,0 +1,319 @@
+use crate::character::{Character, heal};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatusKind {
+    Poison,
+    Burn,
+    Bleed,
+    Stun,
+    Slow,
+    Regeneration,
+    Shield,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StackingRule {
+    Stack { max_stacks: u32 }, // Each application adds a stack, duration refreshes
+    Additive,                  // Potency of every application is summed
+    Refresh,                   // Duration and potency take the stronger of old and new
+    Ignore,                    // Cannot be reapplied while active
+}
+
+impl StatusKind {
+    pub fn get_stacking_rule(&self) -> StackingRule {
+        match self {
+            StatusKind::Poison => StackingRule::Stack { max_stacks: 5 },
+            StatusKind::Bleed => StackingRule::Stack { max_stacks: 3 },
+            StatusKind::Shield => StackingRule::Additive,
+            StatusKind::Burn | StatusKind::Slow | StatusKind::Regeneration => StackingRule::Refresh,
+            StatusKind::Stun => StackingRule::Ignore,
+        }
+    }
+
+    pub fn is_harmful(&self) -> bool {
+        !matches!(self, StatusKind::Regeneration | StatusKind::Shield)
+    }
+
+    // Reviving cleanses every harmful effect but keeps buffs
+    pub fn persists_through_revive(&self) -> bool {
+        !self.is_harmful()
+    }
+
+    // Stun lasts a number of skipped turns rather than rounds, so a stun landed after the
+    // target already acted still costs it a turn
+    fn counts_turns(&self) -> bool {
+        matches!(self, StatusKind::Stun)
+    }
+
+    fn ticks(&self) -> bool {
+        matches!(
+            self,
+            StatusKind::Poison | StatusKind::Burn | StatusKind::Bleed | StatusKind::Regeneration
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub potency: u32,
+    pub duration: u32, // Rounds remaining, or skipped turns for Stun
+    pub stacks: u32,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusKind, potency: u32, duration: u32) -> Self {
+        StatusEffect {
+            kind,
+            potency,
+            duration,
+            stacks: 1,
+        }
+    }
+
+    pub fn get_tick_amount(&self) -> u32 {
+        self.potency * self.stacks
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StatusTick {
+    pub kind: StatusKind,
+    pub amount: u32,
+    pub health_after: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StatusEffects {
+    effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    pub fn apply(&mut self, effect: StatusEffect) {
+        let Some(existing) = self.effects.iter_mut().find(|e| e.kind == effect.kind) else {
+            self.effects.push(effect);
+            return;
+        };
+
+        match effect.kind.get_stacking_rule() {
+            StackingRule::Stack { max_stacks } => {
+                existing.stacks = (existing.stacks + effect.stacks).min(max_stacks);
+                existing.potency = existing.potency.max(effect.potency);
+                existing.duration = existing.duration.max(effect.duration);
+            }
+            StackingRule::Additive => {
+                existing.potency += effect.potency;
+                existing.duration = existing.duration.max(effect.duration);
+            }
+            StackingRule::Refresh => {
+                existing.potency = existing.potency.max(effect.potency);
+                existing.duration = existing.duration.max(effect.duration);
+            }
+            StackingRule::Ignore => {}
+        }
+    }
+
+    pub fn get(&self, kind: StatusKind) -> Option<&StatusEffect> {
+        self.effects.iter().find(|e| e.kind == kind)
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.get(kind).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StatusEffect> {
+        self.effects.iter()
+    }
+
+    // Slowed characters move, aim and dodge worse, by potency percent
+    pub fn get_slow_factor(&self) -> f32 {
+        self.get(StatusKind::Slow)
+            .map_or(1.0, |slow| 1.0 - (slow.potency.min(100) as f32 / 100.0))
+    }
+
+    // Shields soak damage before it reaches health; returns what gets through
+    pub fn absorb(&mut self, damage: u32) -> u32 {
+        let Some(shield) = self
+            .effects
+            .iter_mut()
+            .find(|e| e.kind == StatusKind::Shield)
+        else {
+            return damage;
+        };
+
+        let absorbed = shield.potency.min(damage);
+        shield.potency -= absorbed;
+        if shield.potency == 0 {
+            self.effects.retain(|e| e.kind != StatusKind::Shield);
+        }
+        damage - absorbed
+    }
+
+    // Spends one turn of a turn-counted effect such as Stun
+    pub fn consume_turn(&mut self, kind: StatusKind) {
+        if let Some(effect) = self.effects.iter_mut().find(|e| e.kind == kind) {
+            effect.duration = effect.duration.saturating_sub(1);
+        }
+        self.effects.retain(|e| e.kind != kind || e.duration > 0);
+    }
+
+    // Counts down durations and returns the effects that wore off
+    pub fn end_round(&mut self) -> Vec<StatusKind> {
+        for effect in &mut self.effects {
+            if !effect.kind.counts_turns() {
+                effect.duration = effect.duration.saturating_sub(1);
+            }
+        }
+        let expired = self
+            .effects
+            .iter()
+            .filter(|e| e.duration == 0)
+            .map(|e| e.kind)
+            .collect();
+        self.effects.retain(|e| e.duration > 0);
+        expired
+    }
+
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+
+    pub fn on_revive(&mut self) {
+        self.effects.retain(|e| e.kind.persists_through_revive());
+    }
+}
+
+// Damage over time and regeneration, resolved at the start of each round
+pub fn tick_status_effects<C: Character + ?Sized>(character: &mut C) -> Vec<StatusTick> {
+    let pending: Vec<(StatusKind, u32)> = character
+        .get_status_effects()
+        .iter()
+        .filter(|e| e.kind.ticks())
+        .map(|e| (e.kind, e.get_tick_amount()))
+        .collect();
+
+    let mut ticks = Vec::with_capacity(pending.len());
+    for (kind, amount) in pending {
+        if !character.is_alive() {
+            break;
+        }
+        if kind.is_harmful() {
+            character.set_health(character.get_health().saturating_sub(amount));
+        } else {
+            heal(character, amount);
+        }
+        ticks.push(StatusTick {
+            kind,
+            amount,
+            health_after: character.get_health(),
+        });
+    }
+    ticks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::players::PlayerWarrior;
+
+    #[test]
+    fn stacks_are_capped() {
+        let mut effects = StatusEffects::default();
+        for _ in 0..8 {
+            effects.apply(StatusEffect::new(StatusKind::Poison, 2, 3));
+        }
+        effects.apply(StatusEffect::new(StatusKind::Poison, 4, 1));
+
+        let poison = effects.get(StatusKind::Poison).unwrap();
+        assert_eq!((poison.stacks, poison.potency, poison.duration), (5, 4, 3));
+        assert_eq!(poison.get_tick_amount(), 20);
+    }
+
+    #[test]
+    fn shields_add_up_and_soak_damage() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusEffect::new(StatusKind::Shield, 10, 2));
+        effects.apply(StatusEffect::new(StatusKind::Shield, 5, 4));
+        assert_eq!(effects.get(StatusKind::Shield).unwrap().duration, 4);
+
+        assert_eq!(effects.absorb(12), 0);
+        assert_eq!(effects.get(StatusKind::Shield).unwrap().potency, 3);
+        assert_eq!(effects.absorb(10), 7);
+        assert!(!effects.has(StatusKind::Shield));
+        assert_eq!(effects.absorb(10), 10);
+    }
+
+    #[test]
+    fn refreshing_keeps_the_stronger_values() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusEffect::new(StatusKind::Burn, 8, 1));
+        effects.apply(StatusEffect::new(StatusKind::Burn, 3, 4));
+
+        let burn = effects.get(StatusKind::Burn).unwrap();
+        assert_eq!((burn.stacks, burn.potency, burn.duration), (1, 8, 4));
+    }
+
+    #[test]
+    fn stuns_count_turns_not_rounds() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusEffect::new(StatusKind::Stun, 0, 1));
+        effects.apply(StatusEffect::new(StatusKind::Stun, 0, 5));
+        assert_eq!(effects.get(StatusKind::Stun).unwrap().duration, 1);
+
+        assert!(effects.end_round().is_empty());
+        assert!(effects.has(StatusKind::Stun));
+        effects.consume_turn(StatusKind::Stun);
+        assert!(!effects.has(StatusKind::Stun));
+    }
+
+    #[test]
+    fn effects_wear_off_at_the_end_of_a_round() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusEffect::new(StatusKind::Slow, 50, 1));
+        effects.apply(StatusEffect::new(StatusKind::Bleed, 3, 2));
+        assert_eq!(effects.get_slow_factor(), 0.5);
+
+        assert_eq!(effects.end_round(), vec![StatusKind::Slow]);
+        assert_eq!(effects.get_slow_factor(), 1.0);
+        assert_eq!(effects.end_round(), vec![StatusKind::Bleed]);
+        assert_eq!(effects.iter().count(), 0);
+    }
+
+    #[test]
+    fn reviving_keeps_only_buffs() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusEffect::new(StatusKind::Poison, 2, 3));
+        effects.apply(StatusEffect::new(StatusKind::Stun, 0, 1));
+        effects.apply(StatusEffect::new(StatusKind::Regeneration, 5, 3));
+        effects.apply(StatusEffect::new(StatusKind::Shield, 10, 3));
+
+        effects.on_revive();
+        let kinds: Vec<_> = effects.iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![StatusKind::Regeneration, StatusKind::Shield]);
+    }
+
+    #[test]
+    fn ticks_stop_at_zero_health() {
+        let mut target = PlayerWarrior::new("Pippin");
+        target.set_health(10);
+        target.apply_status(StatusEffect::new(StatusKind::Burn, 25, 2));
+        target.apply_status(StatusEffect::new(StatusKind::Regeneration, 5, 2));
+
+        let ticks = tick_status_effects(&mut target);
+        assert_eq!(ticks.len(), 1);
+        assert_eq!((ticks[0].amount, ticks[0].health_after), (25, 0));
+        assert!(!target.is_alive());
+    }
+
+    #[test]
+    fn regeneration_heals_up_to_max_health() {
+        let mut target = PlayerWarrior::new("Pippin");
+        let max_health = target.get_calculated_max_health();
+        target.set_health(max_health - 2);
+        target.apply_status(StatusEffect::new(StatusKind::Regeneration, 5, 2));
+
+        let ticks = tick_status_effects(&mut target);
+        assert_eq!((ticks[0].amount, ticks[0].health_after), (5, max_health));
+        assert_eq!(target.get_health(), max_health);
+    }
+}
@@ -0,0 +1,621 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::abilities::ability_by_id;
+use crate::actor::{Actor, ActorTemplate};
+use crate::character::CharacterType;
+use crate::classes::{AttackType, ClassType, DamageType, Mage, Warrior};
+use crate::elements::Affinity;
+use crate::items::InventoryError;
+use crate::quests::quest_by_id;
+use crate::save::decode_character_type;
+
+const KNOWN_KEYS: &[&str] = &[
+    "name",
+    "type",
+    "class",
+    "class.strength",
+    "class.mana",
+    "class.power",
+    "health",
+    "damage",
+    "damage_type",
+    "attack_type",
+    "attack",
+    "attack.cost",
+    "mana",
+    "armor",
+    "magic_resistance",
+    "speed",
+    "aggro",
+    "flee_threshold",
+    "invincible_above",
+    "passive",
+    "trades",
+    "gives_quests",
+    "gold",
+    "dialogue",
+    "abilities",
+    "items",
+    "quests",
+    "weak",
+    "resistant",
+    "immune",
+];
+
+// Every error carries the line of the data file it was found on
+#[derive(Debug)]
+pub enum TemplateError {
+    Io(io::Error),
+    Syntax {
+        line: usize,
+        content: String,
+    },
+    OutsideTemplate {
+        line: usize,
+    }, // A key before the first [id] header
+    DuplicateTemplate {
+        line: usize,
+        id: String,
+    },
+    UnknownKey {
+        line: usize,
+        key: String,
+    },
+    DuplicateKey {
+        line: usize,
+        key: String,
+    },
+    InvalidValue {
+        line: usize,
+        key: String,
+        value: String,
+    },
+    UnknownAbility {
+        line: usize,
+        ability: String,
+    },
+    UnknownQuest {
+        line: usize,
+        quest: String,
+    },
+    // An unknown item, or more than the template's pack can hold
+    Items {
+        line: usize,
+        error: InventoryError,
+    },
+    MissingField {
+        line: usize,
+        id: String,
+        field: String,
+    }, // Points at the header
+}
+
+impl TemplateError {
+    // 0 when the file could not be read at all
+    pub fn line(&self) -> usize {
+        match self {
+            TemplateError::Io(_) => 0,
+            TemplateError::Syntax { line, .. }
+            | TemplateError::OutsideTemplate { line }
+            | TemplateError::DuplicateTemplate { line, .. }
+            | TemplateError::UnknownKey { line, .. }
+            | TemplateError::DuplicateKey { line, .. }
+            | TemplateError::InvalidValue { line, .. }
+            | TemplateError::UnknownAbility { line, .. }
+            | TemplateError::UnknownQuest { line, .. }
+            | TemplateError::Items { line, .. }
+            | TemplateError::MissingField { line, .. } => *line,
+        }
+    }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TemplateError::Io(error) => write!(f, "could not read template file: {}", error),
+            TemplateError::Syntax { line, content } => {
+                write!(
+                    f,
+                    "line {}: expected `key = value`, got {:?}",
+                    line, content
+                )
+            }
+            TemplateError::OutsideTemplate { line } => {
+                write!(f, "line {}: field outside of any [template]", line)
+            }
+            TemplateError::DuplicateTemplate { line, id } => {
+                write!(f, "line {}: template `{}` is defined twice", line, id)
+            }
+            TemplateError::UnknownKey { line, key } => {
+                write!(f, "line {}: unknown field `{}`", line, key)
+            }
+            TemplateError::DuplicateKey { line, key } => {
+                write!(f, "line {}: field `{}` is set twice", line, key)
+            }
+            TemplateError::InvalidValue { line, key, value } => {
+                write!(
+                    f,
+                    "line {}: field `{}` has invalid value {:?}",
+                    line, key, value
+                )
+            }
+            TemplateError::UnknownAbility { line, ability } => {
+                write!(f, "line {}: unknown ability `{}`", line, ability)
+            }
+            TemplateError::UnknownQuest { line, quest } => {
+                write!(f, "line {}: unknown quest `{}`", line, quest)
+            }
+            TemplateError::Items { line, error } => write!(f, "line {}: {}", line, error),
+            TemplateError::MissingField { line, id, field } => {
+                write!(f, "line {}: template `{}` is missing `{}`", line, id, field)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TemplateError::Io(error) => Some(error),
+            TemplateError::Items { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+// One `[id]` block of a data file, before its values are checked
+struct Section {
+    id: String,
+    line: usize,
+    fields: Vec<Field>,
+}
+
+struct Field {
+    key: String,
+    value: String,
+    line: usize,
+}
+
+impl Section {
+    fn find(&self, key: &str) -> Option<&Field> {
+        self.fields.iter().find(|field| field.key == key)
+    }
+
+    fn require(&self, key: &str) -> Result<&Field, TemplateError> {
+        self.find(key).ok_or_else(|| self.missing(key))
+    }
+
+    fn missing(&self, key: &str) -> TemplateError {
+        TemplateError::MissingField {
+            line: self.line,
+            id: self.id.clone(),
+            field: key.to_string(),
+        }
+    }
+
+    fn decode<T>(
+        &self,
+        key: &str,
+        decode: impl Fn(&str) -> Option<T>,
+    ) -> Result<Option<T>, TemplateError> {
+        self.find(key)
+            .map(|field| field.decode(&decode))
+            .transpose()
+    }
+
+    fn number(&self, key: &str) -> Result<Option<u32>, TemplateError> {
+        self.decode(key, |value| value.parse().ok())
+    }
+
+    fn flag(&self, key: &str) -> Result<bool, TemplateError> {
+        Ok(self
+            .decode(key, |value| value.parse().ok())?
+            .unwrap_or(false))
+    }
+}
+
+impl Field {
+    fn decode<T>(&self, decode: impl Fn(&str) -> Option<T>) -> Result<T, TemplateError> {
+        decode(&self.value).ok_or_else(|| self.invalid())
+    }
+
+    fn invalid(&self) -> TemplateError {
+        TemplateError::InvalidValue {
+            line: self.line,
+            key: self.key.clone(),
+            value: self.value.clone(),
+        }
+    }
+
+    // Comma separated, e.g. `abilities = shield_bash, second_wind`
+    fn items(&self) -> impl Iterator<Item = &str> {
+        self.value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+    }
+}
+
+fn decode_damage_type(value: &str) -> Option<DamageType> {
+    match value {
+        "Physical" => Some(DamageType::Physical),
+        "Slash" => Some(DamageType::Slash),
+        "Pierce" => Some(DamageType::Pierce),
+        "Blunt" => Some(DamageType::Blunt),
+        "Magical" => Some(DamageType::Magical),
+        "Fire" => Some(DamageType::Fire),
+        "Frost" => Some(DamageType::Frost),
+        "Lightning" => Some(DamageType::Lightning),
+        "Poison" => Some(DamageType::Poison),
+        "Holy" => Some(DamageType::Holy),
+        "Shadow" => Some(DamageType::Shadow),
+        _ => None,
+    }
+}
+
+fn decode_attack_type(value: &str) -> Option<AttackType> {
+    match value {
+        "Melee" => Some(AttackType::Melee),
+        "Ranged" => Some(AttackType::Ranged),
+        _ => None,
+    }
+}
+
+// A fraction of max health, from 0 to 1
+fn decode_share(value: &str) -> Option<f32> {
+    value
+        .parse()
+        .ok()
+        .filter(|share: &f32| (0.0..=1.0).contains(share))
+}
+
+fn read_class(section: &Section) -> Result<ClassType, TemplateError> {
+    let Some(field) = section.find("class") else {
+        return Ok(ClassType::Warrior(Warrior::new()));
+    };
+    match field.value.as_str() {
+        "warrior" => {
+            let strength = section.number("class.strength")?;
+            Ok(ClassType::Warrior(Warrior::with_strength(
+                strength.unwrap_or_else(|| Warrior::new().get_strength()),
+            )))
+        }
+        "mage" => {
+            let defaults = Mage::new();
+            let mana = section.number("class.mana")?;
+            let power = section.number("class.power")?;
+            Ok(ClassType::Mage(Mage::with_stats(
+                mana.unwrap_or(defaults.get_mana()),
+                power.unwrap_or(defaults.get_power()),
+            )))
+        }
+        _ => Err(field.invalid()),
+    }
+}
+
+fn build_template(section: &Section) -> Result<ActorTemplate, TemplateError> {
+    let name = section.require("name")?;
+    let mut template = ActorTemplate::new(&section.id, &name.value);
+
+    template.character_type = section.require("type")?.decode(decode_character_type)?;
+    template.class = read_class(section)?;
+    template.base_health = section
+        .require("health")?
+        .decode(|value| value.parse().ok().filter(|&health: &u32| health > 0))?;
+    template.base_damage = section
+        .require("damage")?
+        .decode(|value| value.parse().ok())?;
+
+    if let Some(damage_type) = section.decode("damage_type", decode_damage_type)? {
+        template.damage_type = damage_type;
+    }
+    if let Some(attack_type) = section.decode("attack_type", decode_attack_type)? {
+        template.attack_type = attack_type;
+    }
+    template.attack_name = section.find("attack").map(|field| field.value.clone());
+    template.attack_cost = section.number("attack.cost")?.unwrap_or(0);
+    template.mana = section.number("mana")?;
+    // An attack that costs more than the whole mana pool could never be used
+    if let Some(field) = section.find("attack.cost") {
+        let pool = template.mana.or(template.class.get_mana()).unwrap_or(0);
+        if template.attack_cost > pool {
+            return Err(field.invalid());
+        }
+    }
+    template.armor = section.number("armor")?;
+    template.magic_resistance = section.number("magic_resistance")?;
+    template.speed = section.decode("speed", |value| {
+        value.parse().ok().filter(|&speed: &u32| speed > 0)
+    })?;
+    if let Some(aggro) = section.decode("aggro", |value| {
+        value.parse().ok().filter(|&aggro: &u32| aggro <= 100)
+    })? {
+        template.aggro = aggro;
+    }
+    if let Some(threshold) = section.decode("flee_threshold", decode_share)? {
+        template.flee_threshold = threshold;
+    }
+    template.invincible_above = section.decode("invincible_above", decode_share)?;
+    template.passive = section.flag("passive")?;
+    template.trades = section.flag("trades")?;
+    template.gives_quests = section.flag("gives_quests")?;
+    template.gold = section.number("gold")?.unwrap_or(0);
+
+    // NPCs exist to be talked to
+    template.dialogue = match (&template.character_type, section.find("dialogue")) {
+        (_, Some(field)) => Some(field.value.clone()),
+        (CharacterType::NPC { .. }, None) => return Err(section.missing("dialogue")),
+        _ => None,
+    };
+
+    if let Some(field) = section.find("abilities") {
+        for id in field.items() {
+            if ability_by_id(id, 0).is_none() {
+                return Err(TemplateError::UnknownAbility {
+                    line: field.line,
+                    ability: id.to_string(),
+                });
+            }
+            template.abilities.push(id.to_string());
+        }
+    }
+
+    // `items = health_potion:3, ancient_key`, packed into a scratch inventory so the
+    // same limits apply as on spawn
+    if let Some(field) = section.find("items") {
+        let mut inventory = template.empty_inventory();
+        for item in field.items() {
+            let (id, count) = match item.split_once(':') {
+                Some((id, count)) => (
+                    id.trim(),
+                    count
+                        .trim()
+                        .parse()
+                        .ok()
+                        .filter(|&count: &u32| count > 0)
+                        .ok_or_else(|| field.invalid())?,
+                ),
+                None => (item, 1),
+            };
+            inventory
+                .add_by_id(id, count)
+                .map_err(|error| TemplateError::Items {
+                    line: field.line,
+                    error,
+                })?;
+            template.items.push((id.to_string(), count));
+        }
+    }
+
+    if let Some(field) = section.find("quests") {
+        for id in field.items() {
+            if quest_by_id(id).is_none() {
+                return Err(TemplateError::UnknownQuest {
+                    line: field.line,
+                    quest: id.to_string(),
+                });
+            }
+            template.quests.push(id.to_string());
+        }
+    }
+
+    let affinity_keys = [
+        ("weak", Affinity::Weak),
+        ("resistant", Affinity::Resistant),
+        ("immune", Affinity::Immune),
+    ];
+    for (key, affinity) in affinity_keys {
+        if let Some(field) = section.find(key) {
+            for item in field.items() {
+                let damage_type = decode_damage_type(item).ok_or_else(|| field.invalid())?;
+                template.affinities.push((damage_type, affinity));
+            }
+        }
+    }
+
+    Ok(template)
+}
+
+// Splits a data file into sections, recording every line-level problem on the way
+fn read_sections(text: &str, errors: &mut Vec<TemplateError>) -> Vec<Section> {
+    let mut sections: Vec<Section> = Vec::new();
+    for (index, raw) in text.lines().enumerate() {
+        let line = index + 1;
+        let content = raw.trim();
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+
+        if let Some(id) = content
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            let id = id.trim();
+            if id.is_empty() {
+                errors.push(TemplateError::Syntax {
+                    line,
+                    content: raw.to_string(),
+                });
+            } else if sections.iter().any(|section| section.id == id) {
+                errors.push(TemplateError::DuplicateTemplate {
+                    line,
+                    id: id.to_string(),
+                });
+            }
+            sections.push(Section {
+                id: id.to_string(),
+                line,
+                fields: Vec::new(),
+            });
+            continue;
+        }
+
+        let Some((key, value)) = content.split_once('=') else {
+            errors.push(TemplateError::Syntax {
+                line,
+                content: raw.to_string(),
+            });
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        let Some(section) = sections.last_mut() else {
+            errors.push(TemplateError::OutsideTemplate { line });
+            continue;
+        };
+        if !KNOWN_KEYS.contains(&key) {
+            errors.push(TemplateError::UnknownKey {
+                line,
+                key: key.to_string(),
+            });
+        } else if section.find(key).is_some() {
+            errors.push(TemplateError::DuplicateKey {
+                line,
+                key: key.to_string(),
+            });
+        } else {
+            section.fields.push(Field {
+                key: key.to_string(),
+                value: value.to_string(),
+                line,
+            });
+        }
+    }
+    sections
+}
+
+// Actor templates read from a data file
+#[derive(Debug, Clone, Default)]
+pub struct TemplateLibrary {
+    templates: Vec<ActorTemplate>,
+}
+
+impl TemplateLibrary {
+    // Reports the first problem of every template at once, plus every malformed line
+    pub fn parse(text: &str) -> Result<Self, Vec<TemplateError>> {
+        let mut errors = Vec::new();
+        let sections = read_sections(text, &mut errors);
+
+        let mut templates = Vec::new();
+        for section in &sections {
+            match build_template(section) {
+                Ok(template) => templates.push(template),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(TemplateLibrary { templates })
+        } else {
+            errors.sort_by_key(TemplateError::line);
+            Err(errors)
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Vec<TemplateError>> {
+        let text = fs::read_to_string(path).map_err(|error| vec![TemplateError::Io(error)])?;
+        Self::parse(&text)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ActorTemplate> {
+        self.templates.iter().find(|template| template.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ActorTemplate> {
+        self.templates.iter()
+    }
+
+    pub fn spawn(&self, id: &str) -> Option<Actor> {
+        self.get(id).map(ActorTemplate::spawn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHAMAN: &str = "\
+# A caster
+[goblin_shaman]
+name = Goblin Shaman
+type = enemy:4
+class = mage
+class.mana = 80
+health = 60
+damage = 18
+attack = Venom Bolt
+attack.cost = 10
+speed = 11
+";
+
+    fn parse_errors(text: &str) -> Vec<TemplateError> {
+        TemplateLibrary::parse(text).unwrap_err()
+    }
+
+    #[test]
+    fn a_valid_file_loads_every_template() {
+        let library = TemplateLibrary::parse(SHAMAN).unwrap();
+        let shaman = library.get("goblin_shaman").unwrap();
+        assert_eq!(shaman.name, "Goblin Shaman");
+        assert_eq!(shaman.attack_cost, 10);
+        assert_eq!(shaman.speed, Some(11));
+        assert_eq!(library.iter().count(), 1);
+    }
+
+    #[test]
+    fn unknown_keys_report_their_line() {
+        let errors = parse_errors(&format!("{}colour = green\n", SHAMAN));
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], TemplateError::UnknownKey { key, .. } if key == "colour"));
+        assert_eq!(errors[0].line(), 12);
+    }
+
+    #[test]
+    fn fields_need_a_section() {
+        let errors = parse_errors(&format!("name = Stray\n{}", SHAMAN));
+        assert!(matches!(
+            errors[0],
+            TemplateError::OutsideTemplate { line: 1 }
+        ));
+    }
+
+    #[test]
+    fn missing_fields_point_at_the_header() {
+        let errors = parse_errors(&SHAMAN.replace("health = 60\n", ""));
+        assert!(
+            matches!(&errors[0], TemplateError::MissingField { field, .. } if field == "health")
+        );
+        assert_eq!(errors[0].line(), 2);
+    }
+
+    #[test]
+    fn bad_numbers_report_their_line() {
+        let errors = parse_errors(&SHAMAN.replace("damage = 18", "damage = lots"));
+        assert!(
+            matches!(&errors[0], TemplateError::InvalidValue { key, value, .. } if key == "damage" && value == "lots")
+        );
+        assert_eq!(errors[0].line(), 8);
+    }
+
+    #[test]
+    fn speed_must_be_positive() {
+        let errors = parse_errors(&SHAMAN.replace("speed = 11", "speed = 0"));
+        assert!(matches!(&errors[0], TemplateError::InvalidValue { key, .. } if key == "speed"));
+        assert_eq!(errors[0].line(), 11);
+    }
+
+    #[test]
+    fn attacks_must_fit_in_the_mana_pool() {
+        let errors = parse_errors(&SHAMAN.replace("attack.cost = 10", "attack.cost = 90"));
+        assert!(
+            matches!(&errors[0], TemplateError::InvalidValue { key, .. } if key == "attack.cost")
+        );
+        assert_eq!(errors[0].line(), 10);
+
+        // Warriors have no pool at all
+        let warrior = SHAMAN.replace("class = mage\nclass.mana = 80\n", "");
+        assert_eq!(parse_errors(&warrior)[0].line(), 8);
+    }
+}
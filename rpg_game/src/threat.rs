@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+use crate::character::Character;
+
+// Healing draws less attention than hurting the enemy directly
+pub const HEALING_THREAT_FACTOR: f32 = 0.5;
+
+// How much threat each opponent has built up with one enemy, keyed by the opponent's
+// index in its party so namesakes keep their own threat
+#[derive(Debug, Clone, Default)]
+pub struct ThreatTable {
+    threat: HashMap<usize, u32>,
+}
+
+impl ThreatTable {
+    // Damage dealt and taunts count in full
+    pub fn add(&mut self, source: usize, amount: u32) {
+        if amount == 0 {
+            return;
+        }
+        *self.threat.entry(source).or_insert(0) += amount;
+    }
+
+    pub fn add_healing(&mut self, source: usize, healing: u32) {
+        self.add(source, (healing as f32 * HEALING_THREAT_FACTOR) as u32);
+    }
+
+    pub fn get(&self, source: usize) -> u32 {
+        self.threat.get(&source).copied().unwrap_or(0)
+    }
+
+    pub fn total(&self) -> u32 {
+        self.threat.values().sum()
+    }
+
+    // Highest threat first, ties broken by index so the listing is stable
+    pub fn ranked(&self) -> Vec<(usize, u32)> {
+        let mut ranked: Vec<(usize, u32)> = self
+            .threat
+            .iter()
+            .map(|(source, amount)| (*source, *amount))
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        ranked
+    }
+
+    pub fn leader(&self) -> Option<(usize, u32)> {
+        self.ranked().into_iter().next()
+    }
+
+    pub fn clear(&mut self) {
+        self.threat.clear();
+    }
+
+    // Aggro (0-100) is how much the enemy follows the threat table; the rest of its
+    // attention goes to whoever is closest to falling. Returns None until anyone has
+    // generated threat, so callers can fall back to their own targeting.
+    pub fn select_target<C: Character>(&self, aggro: u32, candidates: &[C]) -> Option<usize> {
+        let total = candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.is_alive())
+            .map(|(index, _)| self.get(index))
+            .sum::<u32>();
+        if total == 0 {
+            return None;
+        }
+
+        let temperament = aggro.min(100) as f32 / 100.0;
+        let score = |index: usize, candidate: &C| {
+            let threat_share = self.get(index) as f32 / total as f32;
+            let max_health = candidate.get_calculated_max_health().max(1) as f32;
+            let wounds = 1.0 - candidate.get_health() as f32 / max_health;
+            temperament * threat_share + (1.0 - temperament) * wounds
+        };
+
+        candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.is_alive())
+            .max_by(|(a_index, a), (b_index, b)| {
+                score(*a_index, a)
+                    .total_cmp(&score(*b_index, b))
+                    .then_with(|| b_index.cmp(a_index)) // Earlier slot wins ties
+            })
+            .map(|(index, _)| index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::players::PlayerWarrior;
+
+    #[test]
+    fn namesakes_build_their_own_threat() {
+        let party = vec![PlayerWarrior::new("Legolas"), PlayerWarrior::new("Legolas")];
+        let mut table = ThreatTable::default();
+        table.add(1, 30);
+        table.add_healing(0, 20);
+
+        assert_eq!(table.get(0), 10);
+        assert_eq!(table.get(1), 30);
+        assert_eq!(table.ranked(), vec![(1, 30), (0, 10)]);
+        assert_eq!(table.select_target(100, &party), Some(1));
+    }
+
+    #[test]
+    fn nobody_is_targeted_before_threat_builds_up() {
+        let party = vec![PlayerWarrior::new("Legolas")];
+        let table = ThreatTable::default();
+        assert_eq!(table.leader(), None);
+        assert_eq!(table.select_target(100, &party), None);
+    }
+}
@@ -0,0 +1,442 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::actor::Actor;
+use crate::character::Character;
+use crate::items::{InventoryError, Item};
+use crate::npcs::NPC;
+
+pub const DEFAULT_MARKUP: f32 = 1.0; // Merchants sell at the item's value
+pub const DEFAULT_BUYBACK: f32 = 0.5; // and buy back at half of it
+pub const DEMAND_STEP: f32 = 0.05; // Every unit bought raises the price by 5%
+pub const DEMAND_DECAY: f32 = 0.5; // Half of the demand is forgotten each day
+pub const MIN_PRICE_FACTOR: f32 = 0.5;
+pub const MAX_PRICE_FACTOR: f32 = 2.0;
+pub const RESTOCK_DIVISOR: u32 = 4; // A quarter of the full stock comes back per day
+pub const GOLD_PER_REPUTATION: u32 = 50;
+pub const MAX_DISCOUNT: f32 = 0.2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TradeError {
+    NotTrading(String),
+    NoInventory(String),
+    ZeroQuantity,
+    OutOfStock { item: String, available: u32 },
+    NotEnough { item: String, carried: u32 },
+    InsufficientFunds { price: u32, gold: u32 },
+    MerchantCantAfford { price: u32, gold: u32 },
+    Worthless(String),
+    NoRoom(InventoryError),         // The customer can't carry it
+    MerchantNoRoom(InventoryError), // The merchant has no space left
+}
+
+impl fmt::Display for TradeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TradeError::NotTrading(name) => write!(f, "{} isn't trading", name),
+            TradeError::NoInventory(name) => write!(f, "{} can't carry items", name),
+            TradeError::ZeroQuantity => write!(f, "nothing to trade"),
+            TradeError::OutOfStock { item, available } => {
+                write!(f, "out of stock: only {} `{}` left", available, item)
+            }
+            TradeError::NotEnough { item, carried } => {
+                write!(f, "only carrying {} of `{}`", carried, item)
+            }
+            TradeError::InsufficientFunds { price, gold } => {
+                write!(f, "costs {} gold but only {} is left", price, gold)
+            }
+            TradeError::MerchantCantAfford { price, gold } => write!(
+                f,
+                "the merchant can't afford {} gold with {} in the till",
+                price, gold
+            ),
+            TradeError::Worthless(name) => write!(f, "nobody will pay for the {}", name),
+            TradeError::NoRoom(error) => write!(f, "can't take it: {}", error),
+            TradeError::MerchantNoRoom(error) => {
+                write!(f, "the merchant can't take it: {}", error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TradeError {}
+
+// One completed purchase or sale
+#[derive(Debug, Clone)]
+pub struct Trade {
+    pub item: String,
+    pub quantity: u32,
+    pub unit_price: u32,
+    pub total: u32,
+}
+
+// Prices and the rules for trading with a merchant. Every trade is all or nothing:
+// items and gold only change hands once every check has passed.
+#[derive(Debug, Clone)]
+pub struct Shop {
+    markup: f32,
+    buyback: f32,
+    day: u32,
+    demand: HashMap<String, f32>, // Units bought minus units sold back, fading daily
+    trade_volume: HashMap<String, u32>, // Gold each customer has traded, for reputation
+}
+
+impl Default for Shop {
+    fn default() -> Self {
+        Shop {
+            markup: DEFAULT_MARKUP,
+            buyback: DEFAULT_BUYBACK,
+            day: 1,
+            demand: HashMap::new(),
+            trade_volume: HashMap::new(),
+        }
+    }
+}
+
+impl Shop {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_markup(mut self, markup: f32) -> Self {
+        self.markup = markup;
+        self
+    }
+
+    pub fn with_buyback(mut self, buyback: f32) -> Self {
+        self.buyback = buyback;
+        self
+    }
+
+    pub fn with_reputation(mut self, customer: &str, reputation: u32) -> Self {
+        self.trade_volume
+            .insert(customer.to_string(), reputation * GOLD_PER_REPUTATION);
+        self
+    }
+
+    pub fn get_day(&self) -> u32 {
+        self.day
+    }
+
+    pub fn get_reputation(&self, customer: &str) -> u32 {
+        self.trade_volume.get(customer).copied().unwrap_or(0) / GOLD_PER_REPUTATION
+    }
+
+    // 1% off per reputation point, up to MAX_DISCOUNT
+    pub fn get_discount(&self, customer: &str) -> f32 {
+        (self.get_reputation(customer) as f32 / 100.0).min(MAX_DISCOUNT)
+    }
+
+    pub fn get_price_factor(&self, id: &str) -> f32 {
+        let demand = self.demand.get(id).copied().unwrap_or(0.0);
+        (1.0 + demand * DEMAND_STEP).clamp(MIN_PRICE_FACTOR, MAX_PRICE_FACTOR)
+    }
+
+    // What anyone pays the merchant for one, before who's buying is considered
+    pub fn sell_price(&self, item: &Item) -> u32 {
+        (item.value as f32 * self.markup * self.get_price_factor(&item.id)).round() as u32
+    }
+
+    // What the merchant pays a customer for one
+    pub fn buy_price(&self, item: &Item) -> u32 {
+        (item.value as f32 * self.buyback * self.get_price_factor(&item.id)).round() as u32
+    }
+
+    // The sell price for this customer at this merchant
+    pub fn quote(&self, item: &Item, merchant: &Actor, customer: &str) -> u32 {
+        let coefficient = merchant.get_character_type().get_price_coefficient();
+        let discount = 1.0 - self.get_discount(customer);
+        (self.sell_price(item) as f32 * coefficient * discount).round() as u32
+    }
+
+    // Time passes: merchants restock towards their template stock and demand fades
+    pub fn pass_days(&mut self, merchant: &mut Actor, days: u32) {
+        for _ in 0..days {
+            for (id, target) in &merchant.template.items {
+                let missing = target.saturating_sub(merchant.inventory.count(id));
+                let restock = missing.min((target / RESTOCK_DIVISOR).max(1));
+                if restock > 0 {
+                    let _ = merchant.inventory.add_by_id(id, restock);
+                }
+            }
+            for demand in self.demand.values_mut() {
+                *demand *= DEMAND_DECAY;
+            }
+            self.day += 1;
+        }
+    }
+
+    fn record(&mut self, customer: &str, id: &str, units: f32, gold: u32) {
+        *self.demand.entry(id.to_string()).or_default() += units;
+        let volume = self.trade_volume.entry(customer.to_string()).or_default();
+        *volume = volume.saturating_add(gold);
+    }
+
+    // The customer buys from the merchant's stock
+    pub fn buy(
+        &mut self,
+        merchant: &mut Actor,
+        customer: &mut dyn Character,
+        id: &str,
+        quantity: u32,
+    ) -> Result<Trade, TradeError> {
+        check_trade(merchant, quantity)?;
+        let mut stock = merchant.inventory.clone();
+        let available = stock.count(id);
+        let item = stock
+            .remove(id, quantity)
+            .map_err(|_| TradeError::OutOfStock {
+                item: id.to_string(),
+                available,
+            })?;
+
+        let unit_price = self.quote(&item, merchant, customer.get_name());
+        let total = unit_price * quantity;
+        let gold = customer.get_gold();
+        if gold < total {
+            return Err(TradeError::InsufficientFunds { price: total, gold });
+        }
+        let mut pack = customer
+            .get_inventory()
+            .ok_or_else(|| TradeError::NoInventory(customer.get_name().to_string()))?
+            .clone();
+        pack.add(item.clone(), quantity)
+            .map_err(TradeError::NoRoom)?;
+
+        merchant.inventory = stock;
+        merchant.earn_gold(total);
+        if let Some(inventory) = customer.get_inventory_mut() {
+            *inventory = pack;
+        }
+        customer.set_gold(gold - total);
+        self.record(customer.get_name(), id, quantity as f32, total);
+        Ok(Trade {
+            item: item.name,
+            quantity,
+            unit_price,
+            total,
+        })
+    }
+
+    // The customer sells from their own pack to the merchant
+    pub fn sell(
+        &mut self,
+        merchant: &mut Actor,
+        customer: &mut dyn Character,
+        id: &str,
+        quantity: u32,
+    ) -> Result<Trade, TradeError> {
+        check_trade(merchant, quantity)?;
+        let mut pack = customer
+            .get_inventory()
+            .ok_or_else(|| TradeError::NoInventory(customer.get_name().to_string()))?
+            .clone();
+        let carried = pack.count(id);
+        let item = pack
+            .remove(id, quantity)
+            .map_err(|_| TradeError::NotEnough {
+                item: id.to_string(),
+                carried,
+            })?;
+
+        let unit_price = self.buy_price(&item);
+        if unit_price == 0 {
+            return Err(TradeError::Worthless(item.name));
+        }
+        let total = unit_price * quantity;
+        let till = merchant.get_gold();
+        if till < total {
+            return Err(TradeError::MerchantCantAfford {
+                price: total,
+                gold: till,
+            });
+        }
+        let mut stock = merchant.inventory.clone();
+        stock
+            .add(item.clone(), quantity)
+            .map_err(TradeError::MerchantNoRoom)?;
+
+        merchant.inventory = stock;
+        merchant.set_gold(till - total);
+        if let Some(inventory) = customer.get_inventory_mut() {
+            *inventory = pack;
+        }
+        customer.earn_gold(total);
+        self.record(customer.get_name(), id, -(quantity as f32), total);
+        Ok(Trade {
+            item: item.name,
+            quantity,
+            unit_price,
+            total,
+        })
+    }
+}
+
+fn check_trade(merchant: &Actor, quantity: u32) -> Result<(), TradeError> {
+    if !merchant.can_trade() || !merchant.is_alive() {
+        return Err(TradeError::NotTrading(merchant.get_name().to_string()));
+    }
+    if quantity == 0 {
+        return Err(TradeError::ZeroQuantity);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actor::Preset;
+    use crate::items::{Inventory, item_by_id};
+    use crate::players::PlayerWarrior;
+
+    fn customer(gold: u32) -> PlayerWarrior {
+        let mut customer = PlayerWarrior::new("Sam");
+        customer.gold = gold;
+        customer
+    }
+
+    #[test]
+    fn buying_moves_gold_and_items() {
+        let mut shop = Shop::new();
+        let mut merchant = Preset::Merchant.spawn("Butterbur");
+        let mut sam = customer(100);
+
+        let trade = shop
+            .buy(&mut merchant, &mut sam, "health_potion", 2)
+            .unwrap();
+        assert_eq!((trade.unit_price, trade.total), (25, 50));
+        assert_eq!(sam.gold, 50);
+        assert_eq!(sam.inventory.count("health_potion"), 2);
+        assert_eq!(merchant.get_gold(), 1050);
+        assert_eq!(merchant.inventory.count("health_potion"), 8);
+    }
+
+    #[test]
+    fn a_refused_purchase_changes_nothing() {
+        let mut shop = Shop::new();
+        let mut merchant = Preset::Merchant.spawn("Butterbur");
+        let mut sam = customer(40);
+
+        assert_eq!(
+            shop.buy(&mut merchant, &mut sam, "health_potion", 2)
+                .unwrap_err(),
+            TradeError::InsufficientFunds {
+                price: 50,
+                gold: 40
+            }
+        );
+        assert_eq!(
+            shop.buy(&mut merchant, &mut sam, "iron_sword", 2)
+                .unwrap_err(),
+            TradeError::OutOfStock {
+                item: "iron_sword".to_string(),
+                available: 1
+            }
+        );
+        assert_eq!(sam.gold, 40);
+        assert_eq!(sam.inventory.iter().count(), 0);
+        assert_eq!(merchant.get_gold(), 1000);
+        assert_eq!(merchant.inventory.count("health_potion"), 10);
+        assert_eq!(shop.get_price_factor("health_potion"), 1.0);
+    }
+
+    #[test]
+    fn a_full_pack_keeps_the_gold() {
+        let mut shop = Shop::new();
+        let mut merchant = Preset::Merchant.spawn("Butterbur");
+        let mut sam = customer(1000);
+        sam.inventory = Inventory::new(1, 30);
+        sam.inventory.add_by_id("ancient_key", 1).unwrap();
+
+        assert!(matches!(
+            shop.buy(&mut merchant, &mut sam, "health_potion", 1),
+            Err(TradeError::NoRoom(_))
+        ));
+        assert_eq!(sam.gold, 1000);
+        assert_eq!(merchant.inventory.count("health_potion"), 10);
+    }
+
+    #[test]
+    fn selling_pays_the_buyback_price() {
+        let mut shop = Shop::new();
+        let mut merchant = Preset::Merchant.spawn("Butterbur");
+        let mut sam = customer(0);
+        sam.inventory.add_by_id("health_potion", 4).unwrap();
+
+        let trade = shop
+            .sell(&mut merchant, &mut sam, "health_potion", 4)
+            .unwrap();
+        assert_eq!(trade.unit_price, 13);
+        assert_eq!(sam.gold, 52);
+        assert_eq!(sam.inventory.count("health_potion"), 0);
+        assert_eq!(merchant.inventory.count("health_potion"), 14);
+        assert!(shop.get_price_factor("health_potion") < 1.0);
+    }
+
+    #[test]
+    fn refuses_worthless_items_zero_quantities_and_non_traders() {
+        let mut shop = Shop::new();
+        let mut merchant = Preset::Merchant.spawn("Butterbur");
+        let mut villager = Preset::Villager.spawn("Rosie");
+        let mut sam = customer(100);
+        sam.inventory.add_by_id("ancient_key", 1).unwrap();
+
+        assert_eq!(
+            shop.sell(&mut merchant, &mut sam, "ancient_key", 1)
+                .unwrap_err(),
+            TradeError::Worthless("Ancient Key".to_string())
+        );
+        assert_eq!(
+            shop.buy(&mut merchant, &mut sam, "health_potion", 0)
+                .unwrap_err(),
+            TradeError::ZeroQuantity
+        );
+        assert_eq!(
+            shop.buy(&mut villager, &mut sam, "health_potion", 1)
+                .unwrap_err(),
+            TradeError::NotTrading("Rosie".to_string())
+        );
+        assert_eq!(sam.inventory.count("ancient_key"), 1);
+    }
+
+    #[test]
+    fn demand_raises_prices_and_fades_over_days() {
+        let mut shop = Shop::new();
+        let mut merchant = Preset::Merchant.spawn("Butterbur");
+        let mut sam = customer(1000);
+        let potion = item_by_id("health_potion").unwrap();
+
+        shop.buy(&mut merchant, &mut sam, "health_potion", 8)
+            .unwrap();
+        let raised = shop.sell_price(&potion);
+        assert!(raised > 25);
+
+        shop.pass_days(&mut merchant, 3);
+        assert!(shop.sell_price(&potion) < raised);
+        assert!(merchant.inventory.count("health_potion") > 2);
+        assert!(merchant.inventory.count("health_potion") <= 10);
+    }
+
+    #[test]
+    fn reputation_discounts_are_capped() {
+        let shop = Shop::new().with_reputation("Sam", 100);
+        let merchant = Preset::Merchant.spawn("Butterbur");
+        let potion = item_by_id("greater_health_potion").unwrap();
+        assert_eq!(shop.get_discount("Sam"), MAX_DISCOUNT);
+        assert_eq!(shop.quote(&potion, &merchant, "Sam"), 56);
+        assert_eq!(shop.quote(&potion, &merchant, "Pippin"), 70);
+    }
+
+    #[test]
+    fn trade_volume_saturates() {
+        let reputation = u32::MAX / GOLD_PER_REPUTATION;
+        let mut shop = Shop::new().with_reputation("Sam", reputation);
+        let mut merchant = Preset::Merchant.spawn("Butterbur");
+        let mut sam = customer(100);
+
+        shop.buy(&mut merchant, &mut sam, "health_potion", 1)
+            .unwrap();
+        assert_eq!(shop.get_reputation("Sam"), reputation);
+        assert_eq!(shop.get_discount("Sam"), MAX_DISCOUNT);
+    }
+}